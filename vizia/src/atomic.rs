@@ -0,0 +1,125 @@
+// 別のスレッドから更新されるAtomicI32をCounterに表示するためのモジュール
+// ダウンロードの進捗のように，UIスレッドの外で値が変わる場合に使う
+// AtomicI32の変化はviziaに通知されないため，タイマーでおよそ1フレームごとにframeを進め，
+// frameにバインドしたLensがその時点のAtomicI32の値を読み出すことで表示を追従させる
+use std::sync::atomic::{AtomicI32, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use vizia::prelude::*;
+
+use crate::{Counter, CounterModifiers};
+
+// 読み出す間隔．60fpsの1フレーム分とする
+const POLL_INTERVAL: Duration = Duration::from_millis(16);
+
+// タイマーが進めるだけのモデル．frameが変わるたびに，これにバインドしたLensが読み直される
+// 複数のCounterで共有するため，ルートに1つだけ作る
+#[derive(Lens)]
+struct AtomicPoll {
+    frame: u64,
+}
+
+enum AtomicPollEvent {
+    Poll,
+}
+
+impl Model for AtomicPoll {
+    fn event(&mut self, _cx: &mut EventContext, event: &mut Event) {
+        event.map(|poll_event, _| match poll_event {
+            AtomicPollEvent::Poll => {
+                self.frame = self.frame.wrapping_add(1);
+            }
+        });
+    }
+}
+
+// まだ作られていなければAtomicPollをルートに作り，読み出しのタイマーを開始する
+fn ensure_poll(cx: &mut Context) {
+    if cx.data::<AtomicPoll>().is_some() {
+        return;
+    }
+    cx.with_current(Entity::root(), |cx| {
+        AtomicPoll { frame: 0 }.build(cx);
+        let timer = cx.add_timer(POLL_INTERVAL, None, |cx, action| {
+            if let TimerAction::Tick(_) = action {
+                cx.emit(AtomicPollEvent::Poll);
+            }
+        });
+        cx.start_timer(timer);
+    });
+}
+
+impl Counter<i32> {
+    // sharedの値を表示し，ボタンやテキスト入力での変更もsharedに書き込むCounterを作る
+    // 値の正はsharedであり，他のスレッドで書き換えられた値も次のフレームで表示に反映される
+    // 書き込んだ直後にも読み直すため，ボタンを押したときに表示が1フレーム遅れることはない
+    pub fn from_atomic(cx: &mut Context, shared: Arc<AtomicI32>) -> Handle<Self> {
+        ensure_poll(cx);
+        let source = shared.clone();
+        let lens = AtomicPoll::frame.map(move |_| source.load(Ordering::Relaxed));
+        let (increment, decrement, set) = (shared.clone(), shared.clone(), shared.clone());
+        Self::new(cx, lens)
+            .on_increment(move |cx, amount| {
+                increment.fetch_add(amount, Ordering::Relaxed);
+                cx.emit(AtomicPollEvent::Poll);
+            })
+            .on_decrement(move |cx, amount| {
+                decrement.fetch_sub(amount, Ordering::Relaxed);
+                cx.emit(AtomicPollEvent::Poll);
+            })
+            .on_reset(move |cx| {
+                shared.store(0, Ordering::Relaxed);
+                cx.emit(AtomicPollEvent::Poll);
+            })
+            .on_set(move |cx, value| {
+                set.store(value, Ordering::Relaxed);
+                cx.emit(AtomicPollEvent::Poll);
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::CounterHarness;
+    use crate::{AppData, CounterEvent};
+
+    #[test]
+    fn follows_writes_from_another_thread() {
+        let shared = Arc::new(AtomicI32::new(0));
+        let counter_shared = shared.clone();
+        let mut harness = CounterHarness::with_view(AppData::default(), |cx, clock| {
+            Counter::from_atomic(cx, counter_shared)
+                .clock(clock)
+                .entity()
+        });
+        let writer = shared.clone();
+        std::thread::spawn(move || writer.store(42, Ordering::Relaxed))
+            .join()
+            .unwrap();
+        // タイマーが次のフレームでPollを送ったときと同じように読み直させる
+        harness
+            .context()
+            .emit_to(Entity::root(), AtomicPollEvent::Poll);
+        harness.flush();
+        assert_eq!(harness.display(), "42");
+    }
+
+    #[test]
+    fn buttons_write_to_the_shared_value() {
+        let shared = Arc::new(AtomicI32::new(5));
+        let counter_shared = shared.clone();
+        let mut harness = CounterHarness::with_view(AppData::default(), |cx, clock| {
+            Counter::from_atomic(cx, counter_shared)
+                .clock(clock)
+                .entity()
+        });
+        harness.increment();
+        harness.increment();
+        assert_eq!(shared.load(Ordering::Relaxed), 7);
+        assert_eq!(harness.display(), "7");
+        harness.send_counter(CounterEvent::Reset);
+        assert_eq!(shared.load(Ordering::Relaxed), 0);
+    }
+}
@@ -0,0 +1,71 @@
+use vizia::prelude::*;
+
+// 通知の件数のように，値を小さな丸の中に表示するビュー
+// 値が0以下の間は表示しない
+pub struct Badge;
+
+// Badgeに表示する文字列を求める
+// capを超える値は"99+"のようにcapの後に"+"を付けて表示する
+pub fn badge_text(value: i32, cap: Option<i32>) -> String {
+    match cap {
+        Some(cap) if value > cap => format!("{}+", cap),
+        _ => value.to_string(),
+    }
+}
+
+// Badgeを表示するかどうか．0以下のときは何も通知がないため隠す
+pub fn badge_visible(value: i32) -> bool {
+    value > 0
+}
+
+impl View for Badge {
+    fn element(&self) -> Option<&'static str> {
+        Some("badge")
+    }
+}
+
+impl Badge {
+    pub fn new<L>(cx: &mut Context, lens: L) -> Handle<Self>
+    where
+        L: Lens<Target = i32>,
+    {
+        Self::build_badge(cx, lens, None)
+    }
+
+    // capを超える値を"cap+"と表示するBadgeを作る
+    pub fn with_cap<L>(cx: &mut Context, lens: L, cap: i32) -> Handle<Self>
+    where
+        L: Lens<Target = i32>,
+    {
+        Self::build_badge(cx, lens, Some(cap))
+    }
+
+    fn build_badge<L>(cx: &mut Context, lens: L, cap: Option<i32>) -> Handle<Self>
+    where
+        L: Lens<Target = i32>,
+    {
+        Self.build(cx, |cx| {
+            Label::new(cx, lens.map(move |value| badge_text(*value, cap)));
+        })
+        .display(lens.map(|value| badge_visible(*value)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn values_above_the_cap_are_shown_with_a_plus() {
+        assert_eq!(badge_text(99, Some(99)), "99");
+        assert_eq!(badge_text(100, Some(99)), "99+");
+        assert_eq!(badge_text(1000, None), "1000");
+    }
+
+    #[test]
+    fn badge_is_hidden_at_zero() {
+        assert!(!badge_visible(0));
+        assert!(!badge_visible(-1));
+        assert!(badge_visible(1));
+    }
+}
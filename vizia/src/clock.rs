@@ -0,0 +1,51 @@
+// 押した時刻や押していた時間を求めるための時計
+// Counterはcooldownや長押し，繰り返しやdebounceの判定にこの時計を使うため，テストでMockClockに差し替えると
+// 実際に待たなくても時間が経ったときの動作を確認できる
+// viziaのタイマーは判定する機会を作るだけで，次の繰り返しやdebounceの時刻になったかはこの時計で決める
+use std::time::Instant;
+
+#[cfg(test)]
+use std::{cell::Cell, time::Duration};
+
+pub trait Clock {
+    // 現在の時刻を返す
+    fn now(&self) -> Instant;
+}
+
+// システムの時計をそのまま使う．Counterの既定の時計
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+// advanceを呼び出したときだけ進む時計
+#[cfg(test)]
+pub struct MockClock {
+    start: Instant,
+    elapsed: Cell<Duration>,
+}
+
+#[cfg(test)]
+impl MockClock {
+    pub fn new() -> Self {
+        Self {
+            start: Instant::now(),
+            elapsed: Cell::new(Duration::ZERO),
+        }
+    }
+
+    // 時計をdurationだけ進める
+    pub fn advance(&self, duration: Duration) {
+        self.elapsed.set(self.elapsed.get() + duration);
+    }
+}
+
+#[cfg(test)]
+impl Clock for MockClock {
+    fn now(&self) -> Instant {
+        self.start + self.elapsed.get()
+    }
+}
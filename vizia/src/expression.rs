@@ -0,0 +1,194 @@
+// テキスト入力で"3+4"や"10*2"のような簡単な計算式を受け付けるためのモジュール
+// 整数の四則演算と括弧だけを扱い，掛け算と割り算を足し算と引き算より先に計算する
+// 割り算は0の方向に切り捨てる
+//
+// 式   = 項 (("+" | "-") 項)*
+// 項   = 因子 (("*" | "/") 因子)*
+// 因子 = "-" 因子 | "(" 式 ")" | 整数
+
+// 式を計算した結果を返す．式として解釈できない場合や計算できない場合は理由を返す
+pub fn evaluate(text: &str) -> Result<i64, String> {
+    let tokens = tokenize(text)?;
+    if tokens.is_empty() {
+        return Err(String::from("Enter a number"));
+    }
+    let mut parser = Parser { tokens, pos: 0 };
+    let value = parser.expression()?;
+    match parser.peek() {
+        None => Ok(value),
+        Some(token) => Err(format!("Unexpected \"{}\"", token)),
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Token {
+    Number(i64),
+    Plus,
+    Minus,
+    Star,
+    Slash,
+    Open,
+    Close,
+}
+
+impl std::fmt::Display for Token {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Token::Number(value) => write!(f, "{}", value),
+            Token::Plus => write!(f, "+"),
+            Token::Minus => write!(f, "-"),
+            Token::Star => write!(f, "*"),
+            Token::Slash => write!(f, "/"),
+            Token::Open => write!(f, "("),
+            Token::Close => write!(f, ")"),
+        }
+    }
+}
+
+// 文字列を数と記号の並びに分ける．空白は無視する
+fn tokenize(text: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        let token = match c {
+            ' ' | '\t' => continue,
+            '+' => Token::Plus,
+            '-' => Token::Minus,
+            '*' => Token::Star,
+            '/' => Token::Slash,
+            '(' => Token::Open,
+            ')' => Token::Close,
+            '0'..='9' => {
+                let mut digits = c.to_string();
+                while let Some(&next) = chars.peek().filter(|next| next.is_ascii_digit()) {
+                    digits.push(next);
+                    chars.next();
+                }
+                let value = digits
+                    .parse()
+                    .map_err(|_| String::from("Number is too large"))?;
+                Token::Number(value)
+            }
+            _ => return Err(format!("Unexpected \"{}\"", c)),
+        };
+        tokens.push(token);
+    }
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<Token> {
+        self.tokens.get(self.pos).copied()
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.peek();
+        self.pos += 1;
+        token
+    }
+
+    fn expression(&mut self) -> Result<i64, String> {
+        let mut value = self.term()?;
+        while let Some(op @ (Token::Plus | Token::Minus)) = self.peek() {
+            self.next();
+            let rhs = self.term()?;
+            value = match op {
+                Token::Plus => value.checked_add(rhs),
+                _ => value.checked_sub(rhs),
+            }
+            .ok_or_else(overflow)?;
+        }
+        Ok(value)
+    }
+
+    fn term(&mut self) -> Result<i64, String> {
+        let mut value = self.factor()?;
+        while let Some(op @ (Token::Star | Token::Slash)) = self.peek() {
+            self.next();
+            let rhs = self.factor()?;
+            value = match op {
+                Token::Star => value.checked_mul(rhs).ok_or_else(overflow)?,
+                _ if rhs == 0 => return Err(String::from("Cannot divide by zero")),
+                _ => value.checked_div(rhs).ok_or_else(overflow)?,
+            };
+        }
+        Ok(value)
+    }
+
+    fn factor(&mut self) -> Result<i64, String> {
+        match self.next() {
+            Some(Token::Number(value)) => Ok(value),
+            Some(Token::Minus) => self.factor()?.checked_neg().ok_or_else(overflow),
+            Some(Token::Open) => {
+                let value = self.expression()?;
+                match self.next() {
+                    Some(Token::Close) => Ok(value),
+                    _ => Err(String::from("Missing \")\"")),
+                }
+            }
+            Some(token) => Err(format!("Unexpected \"{}\"", token)),
+            None => Err(String::from("Expression is incomplete")),
+        }
+    }
+}
+
+fn overflow() -> String {
+    String::from("Result is too large")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn multiplication_binds_tighter_than_addition() {
+        assert_eq!(evaluate("3+4"), Ok(7));
+        assert_eq!(evaluate("2 + 3 * 4"), Ok(14));
+        assert_eq!(evaluate("(2+3)*10"), Ok(50));
+        assert_eq!(evaluate("10 - 2 - 3"), Ok(5));
+    }
+
+    #[test]
+    fn division_truncates_toward_zero() {
+        assert_eq!(evaluate("7/2"), Ok(3));
+        assert_eq!(evaluate("-7/2"), Ok(-3));
+    }
+
+    #[test]
+    fn unary_minus() {
+        assert_eq!(evaluate("-5"), Ok(-5));
+        assert_eq!(evaluate("--5"), Ok(5));
+        assert_eq!(evaluate("3*-2"), Ok(-6));
+    }
+
+    #[test]
+    fn malformed_expressions_explain_why() {
+        assert_eq!(evaluate(""), Err(String::from("Enter a number")));
+        assert_eq!(
+            evaluate("3+"),
+            Err(String::from("Expression is incomplete"))
+        );
+        assert_eq!(evaluate("(1+2"), Err(String::from("Missing \")\"")));
+        assert_eq!(evaluate("1+2)"), Err(String::from("Unexpected \")\"")));
+        assert_eq!(evaluate("2x"), Err(String::from("Unexpected \"x\"")));
+        assert_eq!(evaluate("1.5"), Err(String::from("Unexpected \".\"")));
+        assert_eq!(evaluate("1/0"), Err(String::from("Cannot divide by zero")));
+    }
+
+    #[test]
+    fn overflow_is_an_error() {
+        assert_eq!(
+            evaluate("99999999999999999999"),
+            Err(String::from("Number is too large"))
+        );
+        assert_eq!(
+            evaluate("9223372036854775807+1"),
+            Err(String::from("Result is too large"))
+        );
+    }
+}
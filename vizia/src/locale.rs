@@ -0,0 +1,171 @@
+// 数値を地域ごとの書式で表示するためのモジュール
+// モデルの値は変更せず，Labelに表示する文字列だけを整形する
+
+// 数値の区切り方
+#[derive(Clone, Copy, PartialEq)]
+pub enum Locale {
+    // 桁区切りを行わない
+    Plain,
+    // 英語(米国)の書式．例: 1,234,567.5
+    EnUs,
+    // ドイツ語の書式．例: 1.234.567,5
+    DeDe,
+}
+
+impl Locale {
+    // 3桁ごとの区切り文字と小数点の文字
+    fn separators(self) -> Option<(char, char)> {
+        match self {
+            Locale::Plain => None,
+            Locale::EnUs => Some((',', '.')),
+            Locale::DeDe => Some(('.', ',')),
+        }
+    }
+}
+
+// to_string()などで作った数値の文字列を，localeの書式に整形する
+// 符号は区切った数字の前に付ける．例: EnUsでは"-1234567"は"-1,234,567"になる
+// 数値として解釈できない文字列はそのまま返す
+pub fn format_number(text: &str, locale: Locale) -> String {
+    let Some((group, decimal)) = locale.separators() else {
+        return text.to_owned();
+    };
+    let (sign, digits) = match text.strip_prefix('-') {
+        Some(rest) => ("-", rest),
+        None => ("", text),
+    };
+    let (integer, fraction) = match digits.split_once('.') {
+        Some((integer, fraction)) => (integer, Some(fraction)),
+        None => (digits, None),
+    };
+    if integer.is_empty() || !integer.chars().all(|c| c.is_ascii_digit()) {
+        return text.to_owned();
+    }
+
+    let mut result = String::from(sign);
+    for (i, c) in integer.chars().enumerate() {
+        // 右から数えて3桁ごとに区切り文字を入れる
+        if i > 0 && (integer.len() - i) % 3 == 0 {
+            result.push(group);
+        }
+        result.push(c);
+    }
+    if let Some(fraction) = fraction {
+        result.push(decimal);
+        result.push_str(fraction);
+    }
+    result
+}
+
+// 負の値の表し方
+#[derive(Clone, Copy, PartialEq)]
+pub enum NegativeStyle {
+    // 先頭にマイナス記号を付ける．例: -5
+    Minus,
+    // 会計の表記のように括弧で囲む．例: (5)
+    Parentheses,
+}
+
+// format_numberで整形した文字列の符号を，styleの表し方に置き換える
+// 負でない値の文字列はそのまま返す
+pub fn format_negative(text: &str, style: NegativeStyle) -> String {
+    match (style, text.strip_prefix('-')) {
+        (NegativeStyle::Parentheses, Some(rest)) => format!("({})", rest),
+        _ => text.to_owned(),
+    }
+}
+
+// 桁の大きな値を省略して表すときの接尾辞と倍率．大きい順に並べる
+const ABBREVIATIONS: [(f64, &str); 4] = [(1e12, "T"), (1e9, "G"), (1e6, "M"), (1e3, "k")];
+
+// 絶対値が1000以上の値を，小数点以下1桁に丸めて"1.2k"や"3.4M"のように省略する
+// 丸めは0から遠い方へ行い，小数点以下が0の場合は"2k"のように省略する
+// 丸めた結果が1000になる場合は次の接尾辞を使う．例: 999950は"1000k"ではなく"1M"になる
+// 1000未満の値は省略しないためNoneを返す
+pub fn abbreviate_number(value: f64, locale: Locale) -> Option<String> {
+    let magnitude = value.abs();
+    if !magnitude.is_finite() {
+        return None;
+    }
+    let index = ABBREVIATIONS
+        .iter()
+        .position(|(scale, _)| magnitude >= *scale)?;
+    let (scale, suffix) = ABBREVIATIONS[index];
+    let rounded = (magnitude / scale * 10.0).round() / 10.0;
+    // 1つ上の接尾辞で表し直す．Tより上の接尾辞はないため，そのまま表示する
+    let (rounded, suffix) = if rounded >= 1000.0 && index > 0 {
+        (1.0, ABBREVIATIONS[index - 1].1)
+    } else {
+        (rounded, suffix)
+    };
+    let sign = if value < 0.0 { "-" } else { "" };
+    let digits = format!("{}{:.1}", sign, rounded);
+    let digits = digits.strip_suffix(".0").unwrap_or(&digits);
+    Some(format!("{}{}", format_number(digits, locale), suffix))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn groups_digits_for_each_locale() {
+        assert_eq!(format_number("1234567", Locale::EnUs), "1,234,567");
+        assert_eq!(format_number("1234567", Locale::DeDe), "1.234.567");
+        assert_eq!(format_number("1234567", Locale::Plain), "1234567");
+    }
+
+    #[test]
+    fn keeps_the_sign_before_the_grouped_digits() {
+        assert_eq!(format_number("-1234567", Locale::EnUs), "-1,234,567");
+        assert_eq!(format_number("-1234567", Locale::DeDe), "-1.234.567");
+    }
+
+    #[test]
+    fn replaces_the_decimal_point() {
+        assert_eq!(format_number("1234567.5", Locale::EnUs), "1,234,567.5");
+        assert_eq!(format_number("1234567.5", Locale::DeDe), "1.234.567,5");
+        assert_eq!(format_number("999", Locale::EnUs), "999");
+    }
+
+    #[test]
+    fn leaves_non_numbers_unchanged() {
+        assert_eq!(format_number("inf", Locale::EnUs), "inf");
+        assert_eq!(format_number("NaN", Locale::DeDe), "NaN");
+    }
+
+    #[test]
+    fn negative_style_chooses_the_sign() {
+        assert_eq!(format_negative("-1,234", NegativeStyle::Minus), "-1,234");
+        assert_eq!(
+            format_negative("-1,234", NegativeStyle::Parentheses),
+            "(1,234)"
+        );
+        // 負でない値はどちらでもそのまま
+        assert_eq!(format_negative("5", NegativeStyle::Minus), "5");
+        assert_eq!(format_negative("5", NegativeStyle::Parentheses), "5");
+    }
+
+    #[test]
+    fn abbreviates_values_from_one_thousand() {
+        assert_eq!(abbreviate_number(999.0, Locale::EnUs), None);
+        assert_eq!(
+            abbreviate_number(1_000.0, Locale::EnUs).as_deref(),
+            Some("1k")
+        );
+        assert_eq!(
+            abbreviate_number(1_500_000.0, Locale::EnUs).as_deref(),
+            Some("1.5M")
+        );
+        // 負の値は符号を残し，絶対値で判定する
+        assert_eq!(abbreviate_number(-999.0, Locale::EnUs), None);
+        assert_eq!(
+            abbreviate_number(-1_500_000.0, Locale::EnUs).as_deref(),
+            Some("-1.5M")
+        );
+        assert_eq!(
+            abbreviate_number(1_500_000.0, Locale::DeDe).as_deref(),
+            Some("1,5M")
+        );
+    }
+}
@@ -1,5 +1,39 @@
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::fs;
+use std::ops::{Add, Mul, Sub};
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicI32, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use num_traits::{NumCast, One};
+use serde::{Deserialize, Serialize};
 use vizia::prelude::*;
 
+mod atomic;
+mod badge;
+mod clock;
+mod expression;
+mod locale;
+mod progress_bar;
+mod rate;
+#[cfg(test)]
+mod snapshot;
+mod spinner;
+mod subscription;
+#[cfg(test)]
+mod testing;
+use badge::Badge;
+use clock::{Clock, SystemClock};
+use locale::{abbreviate_number, format_negative, format_number, Locale, NegativeStyle};
+use progress_bar::ProgressBar;
+use rate::{rate_text, RateTracker};
+use spinner::Spinner;
+use subscription::ChangeSender;
+
 // viziaでの状態はモデルに保存される
 // モデルにはModel traitを実装する任意のデータを保存することができる
 // Lens deriveマクロを使用することでモデルのフィールドにアクセスするためのメソッドを自動生成することができる
@@ -7,6 +41,538 @@ use vizia::prelude::*;
 #[derive(Lens)]
 pub struct AppData {
     pub count: i32,
+    // 読み込んだ時点，または最後に保存した時点のcount
+    pub initial: i32,
+    // countがinitialから変更されているかどうか．保存ボタンの有効・無効にバインドする
+    // countが変わるたびにcount != initialから求め直す
+    pub dirty: bool,
+    // countの下限と上限，1回の操作で変化させる量
+    // AppDataBuilderでmin <= max，step > 0であることが検証される
+    pub min: i32,
+    pub max: i32,
+    pub step: i32,
+    // 小数を扱うCounterの例として重さ(kg)を保持する
+    // 0.1を繰り返し足したときの誤差が溜まらないように，WEIGHT_SCALE倍した整数で保持する
+    // f64としてはweight_lens()やweight()から読み出す
+    pub weight_scaled: i64,
+    // 複数の独立したCounterの値．GUEST_KINDSの各項目に対応する
+    pub counts: Vec<i32>,
+    // AppEvent::Stepを送ったCounterの番号から，変化させるcountsの位置を引くための対応
+    // 対応にない番号のCounterから届いたStepは無視する
+    // バインドする値ではないためLensは生成しない
+    #[lens(ignore)]
+    pub slots: HashMap<CounterId, usize>,
+    // Spinnerの例として部屋数を保持する
+    pub rooms: u32,
+    // 上限から下限へ戻るCounterの例として時刻(0時から23時)を保持する
+    pub hour: i32,
+    // trueの間はcountのCounterを操作できないようにする
+    pub locked: bool,
+    // 現在の配色
+    pub theme: Theme,
+    // Counterのボタンを並べる向き
+    pub direction: LayoutDirection,
+    // countを大きく表示する別のウィンドウを開いているかどうか
+    pub display_window: bool,
+    // countがi32の範囲を超えるときの扱い
+    pub overflow_policy: OverflowPolicy,
+    // 元に戻すための変更前のcountの履歴
+    pub history: Vec<i32>,
+    // 元に戻した操作をやり直すための履歴
+    pub redo: Vec<i32>,
+    // 状態を保存するファイルのパス．Noneの場合は保存しない
+    pub save_path: Option<PathBuf>,
+    // 設定されている場合は，変更のたびに保存せず，最後の変更から一定時間経ってから1回だけ保存する
+    // タイマーはバインドする値ではないためLensは生成しない
+    #[lens(ignore)]
+    pub save_timer: Option<Timer>,
+    // save_timerによる保存を待っている変更があるかどうか
+    pub save_pending: bool,
+    // まだcountに適用していないAddの変化量．届いた順に並ぶ
+    pub pending: Vec<i32>,
+    // スライダーをドラッグしている間，ドラッグを始める前のcountを保持する
+    // ドラッグを終えたときに，この値を1つの変更として履歴に積む
+    pub drag_origin: Option<i32>,
+    // AppData::eventに届いたすべてのイベントを変更の前に受け取る．デバッグやイベントの記録に使う
+    // バインドする値ではないためLensは生成しない
+    #[lens(ignore)]
+    pub on_event: Option<Box<dyn Fn(&AppEvent)>>,
+}
+
+// weightを整数で保持するときの倍率．小数点以下1桁までを正確に扱う
+const WEIGHT_SCALE: f64 = 10.0;
+
+// f64の値をWEIGHT_SCALE倍した整数に丸める
+fn to_scaled(value: f64) -> i64 {
+    (value * WEIGHT_SCALE).round() as i64
+}
+
+fn from_scaled(scaled: i64) -> f64 {
+    scaled as f64 / WEIGHT_SCALE
+}
+
+// アプリケーションを再起動しても値が残るようにファイルに保存する内容
+// 履歴などの一時的な状態は保存しない
+#[derive(Default, Serialize, Deserialize)]
+struct SavedData {
+    #[serde(default)]
+    count: i32,
+    #[serde(default)]
+    weight: f64,
+    #[serde(default)]
+    counts: Vec<i32>,
+}
+
+// アプリケーションの配色
+#[derive(Clone, Copy, PartialEq, Debug, Data)]
+pub enum Theme {
+    Light,
+    Dark,
+}
+
+impl Theme {
+    pub fn toggled(self) -> Self {
+        match self {
+            Theme::Light => Theme::Dark,
+            Theme::Dark => Theme::Light,
+        }
+    }
+
+    // 各配色のスタイルシートはこのクラスを持つ要素の子孫にだけ適用されるように書かれている
+    pub fn class(self) -> &'static str {
+        match self {
+            Theme::Light => "light",
+            Theme::Dark => "dark",
+        }
+    }
+}
+
+// 配色のスタイルシートを切り替える
+// viziaのスタイルシートはアプリケーション全体に追加されるため，両方の配色を最初に読み込んでおき，
+// ルート要素の配色クラスを付け替えることで適用されるスタイルシートを切り替える
+fn apply_theme<V: View>(handle: Handle<V>) -> Handle<V> {
+    handle.bind(AppData::theme, |handle, theme| {
+        let theme = theme.get(&handle);
+        handle
+            .toggle_class(Theme::Light.class(), theme == Theme::Light)
+            .toggle_class(Theme::Dark.class(), theme == Theme::Dark);
+    })
+}
+
+// 複数のCounterの例として，人数を区分ごとに数える
+const GUEST_KINDS: [&str; 3] = ["Adults", "Children", "Infants"];
+// 区分ごとの人数の範囲
+const GUEST_RANGE: (i32, i32) = (0, 9);
+// 部屋数の範囲．Spinnerの範囲にも使う
+const ROOMS_RANGE: (u32, u32) = (1, 5);
+
+// 区分ごとのCounterに割り当てる番号
+// Counter::newが自動的に割り当てる番号とは重ならない
+fn guest_id(index: usize) -> CounterId {
+    CounterId(index as u64)
+}
+
+impl Default for AppData {
+    fn default() -> Self {
+        Self {
+            count: 0,
+            initial: 0,
+            dirty: false,
+            min: i32::MIN,
+            max: i32::MAX,
+            step: 1,
+            weight_scaled: 0,
+            counts: vec![0; GUEST_KINDS.len()],
+            slots: (0..GUEST_KINDS.len())
+                .map(|index| (guest_id(index), index))
+                .collect(),
+            rooms: 1,
+            hour: 0,
+            locked: false,
+            theme: Theme::Light,
+            direction: LayoutDirection::LeftToRight,
+            display_window: false,
+            overflow_policy: OverflowPolicy::Saturate,
+            history: Vec::new(),
+            redo: Vec::new(),
+            save_path: None,
+            save_timer: None,
+            save_pending: false,
+            pending: Vec::new(),
+            drag_origin: None,
+            on_event: None,
+        }
+    }
+}
+
+// AppDataを作成するためのビルダー
+// フィールドが増えても壊れた状態のモデルが作られないように，build()で不変条件を検証する
+pub struct AppDataBuilder {
+    count: i32,
+    min: i32,
+    max: i32,
+    step: i32,
+    on_event: Option<Box<dyn Fn(&AppEvent)>>,
+    overflow_policy: OverflowPolicy,
+}
+
+// 変化させた結果がi32の範囲を超えるときの扱い
+// 範囲を超えない場合はどれも同じ結果になり，その後でmin・maxの範囲に収められる
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum OverflowPolicy {
+    // i32::MAXまたはi32::MINで止める
+    Saturate,
+    // 反対側の端に回り込む
+    Wrap,
+    // 変更せずに元の値のままにする
+    Ignore,
+}
+
+impl OverflowPolicy {
+    // valueにdeltaを加えた値を返す．Ignoreで範囲を超える場合はNoneを返す
+    pub fn add(self, value: i32, delta: i32) -> Option<i32> {
+        match self {
+            OverflowPolicy::Saturate => Some(value.saturating_add(delta)),
+            OverflowPolicy::Wrap => Some(value.wrapping_add(delta)),
+            OverflowPolicy::Ignore => value.checked_add(delta),
+        }
+    }
+
+    // valueからamountを引いた値を返す．Ignoreで範囲を超える場合はNoneを返す
+    pub fn sub(self, value: i32, amount: i32) -> Option<i32> {
+        match self {
+            OverflowPolicy::Saturate => Some(value.saturating_sub(amount)),
+            OverflowPolicy::Wrap => Some(value.wrapping_sub(amount)),
+            OverflowPolicy::Ignore => value.checked_sub(amount),
+        }
+    }
+}
+
+// AppDataBuilder::build()が返すエラー
+#[derive(Debug, PartialEq)]
+pub enum AppDataError {
+    // 下限が上限より大きい
+    InvalidRange { min: i32, max: i32 },
+    // stepが0以下
+    InvalidStep(i32),
+}
+
+impl std::fmt::Display for AppDataError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AppDataError::InvalidRange { min, max } => {
+                write!(f, "min ({}) must not be greater than max ({})", min, max)
+            }
+            AppDataError::InvalidStep(step) => {
+                write!(f, "step ({}) must be greater than 0", step)
+            }
+        }
+    }
+}
+
+impl std::error::Error for AppDataError {}
+
+impl Default for AppDataBuilder {
+    fn default() -> Self {
+        Self {
+            count: 0,
+            min: i32::MIN,
+            max: i32::MAX,
+            step: 1,
+            on_event: None,
+            overflow_policy: OverflowPolicy::Saturate,
+        }
+    }
+}
+
+impl AppDataBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn count(mut self, count: i32) -> Self {
+        self.count = count;
+        self
+    }
+
+    pub fn min(mut self, min: i32) -> Self {
+        self.min = min;
+        self
+    }
+
+    pub fn max(mut self, max: i32) -> Self {
+        self.max = max;
+        self
+    }
+
+    pub fn step(mut self, step: i32) -> Self {
+        self.step = step;
+        self
+    }
+
+    // countがi32の範囲を超えるときの扱いを設定する．設定しない場合はSaturateとなる
+    pub fn overflow_policy(mut self, policy: OverflowPolicy) -> Self {
+        self.overflow_policy = policy;
+        self
+    }
+
+    // AppData::eventに届いたイベントを受け取るフックを設定する
+    pub fn on_event<F: Fn(&AppEvent) + 'static>(mut self, hook: F) -> Self {
+        self.on_event = Some(Box::new(hook));
+        self
+    }
+
+    // 設定された値を検証してAppDataを作成する
+    pub fn build(self) -> Result<AppData, AppDataError> {
+        if self.min > self.max {
+            return Err(AppDataError::InvalidRange {
+                min: self.min,
+                max: self.max,
+            });
+        }
+        if self.step <= 0 {
+            return Err(AppDataError::InvalidStep(self.step));
+        }
+
+        // 初期値も範囲内に収める
+        let count = self.count.clamp(self.min, self.max);
+        Ok(AppData {
+            count,
+            initial: count,
+            min: self.min,
+            max: self.max,
+            step: self.step,
+            on_event: self.on_event,
+            overflow_policy: self.overflow_policy,
+            ..AppData::default()
+        })
+    }
+}
+
+impl AppData {
+    pub fn builder() -> AppDataBuilder {
+        AppDataBuilder::new()
+    }
+
+    // ファイルから状態を読み込む
+    // ファイルが存在しない場合や内容が壊れている場合は初期値を使用する
+    pub fn load(path: &Path) -> Self {
+        Self::default().restore(path)
+    }
+
+    // 前回保存した状態をファイルから読み込んで反映する
+    // 下限・上限などの設定はそのままにし，保存されていた値だけを置き換える
+    pub fn restore(self, path: &Path) -> Self {
+        let saved = match fs::read_to_string(path) {
+            Ok(text) => serde_json::from_str(&text).unwrap_or_else(|err| {
+                eprintln!("Failed to parse {}: {}", path.display(), err);
+                SavedData::default()
+            }),
+            Err(_) => SavedData::default(),
+        };
+
+        // 保存されていた数が異なる場合でも各Counterが自分の要素を持てるように長さを揃える
+        let mut counts = saved.counts;
+        counts.resize(GUEST_KINDS.len(), 0);
+
+        // 保存されていた値が現在の範囲外であっても範囲内に収める
+        let count = self.clamped(saved.count);
+        Self {
+            count,
+            initial: count,
+            dirty: false,
+            weight_scaled: to_scaled(saved.weight),
+            counts,
+            save_path: Some(path.to_path_buf()),
+            ..self
+        }
+    }
+
+    // 変更のたびに保存せず，最後の変更からdelayだけ経ってからまとめて保存するようにする
+    // 素早く連続して変更した場合でも，書き込むのは落ち着いた後の値を1回だけとなる
+    pub fn save_delay(self, cx: &mut Context, delay: Duration) -> Self {
+        let timer = cx.add_timer(delay, None, |cx, action| {
+            if let TimerAction::Tick(_) = action {
+                cx.emit(AppEvent::FlushSave);
+            }
+        });
+        Self {
+            save_timer: Some(timer),
+            ..self
+        }
+    }
+
+    // 保存を待っている変更があればファイルに書き込む
+    fn flush_save(&mut self) {
+        if self.save_pending {
+            self.save_pending = false;
+            self.autosave();
+        }
+    }
+
+    // 変更のたびに行う保存
+    // 書き込みに失敗してもアプリケーションは継続できるため，エラーは表示するだけにする
+    fn autosave(&self) {
+        if let Some(path) = &self.save_path {
+            if let Err(err) = self.save(path) {
+                eprintln!("Failed to save {}: {}", path.display(), err);
+            }
+        }
+    }
+
+    // 現在の状態をファイルに書き込む．失敗した場合は理由を返す
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let saved = SavedData {
+            count: self.count,
+            weight: self.weight(),
+            counts: self.counts.clone(),
+        };
+        serde_json::to_string(&saved)
+            .map_err(|err| err.to_string())
+            .and_then(|text| fs::write(path, text).map_err(|err| err.to_string()))
+    }
+
+    // countsのindex番目の要素にバインドするLensを返す
+    // Counter::newは単一の値へのLensを受け取るため，これを使用して各要素に個別のCounterを割り当てる
+    pub fn count_at(index: usize) -> impl Lens<Source = Self, Target = i32> {
+        AppData::counts.idx(index)
+    }
+
+    // countsの合計を読み出すLensを返す
+    // countsのどの要素が変化しても合計が求め直されるため，Labelにバインドすると常に最新の合計を表示する
+    pub fn counts_total() -> impl Lens<Source = Self, Target = i32> {
+        AppData::counts.map(|counts| counts.iter().sum())
+    }
+
+    // 重さをf64で返す
+    pub fn weight(&self) -> f64 {
+        from_scaled(self.weight_scaled)
+    }
+
+    // 重さをf64として読み出すLensを返す
+    pub fn weight_lens() -> impl Lens<Source = Self, Target = f64> {
+        AppData::weight_scaled.map(|scaled| from_scaled(*scaled))
+    }
+
+    // countを変更する前に呼び出し，現在の値を履歴に積む
+    // 新しい変更が行われた時点でやり直しの履歴は無効になるため破棄する
+    fn record(&mut self) {
+        self.history.push(self.count);
+        self.redo.clear();
+    }
+
+    // 下限・上限の範囲に収める
+    // イベントからの変更もset_countからの変更も同じ範囲に収まるように，countを変更する処理はすべてここを通す
+    fn clamped(&self, value: i32) -> i32 {
+        value.clamp(self.min, self.max)
+    }
+
+    // countを範囲内に収めた値に設定する．イベントによる変更と同様に元に戻すことができる
+    // 範囲に収めた結果が現在の値と同じ場合は変更ではないため，履歴に積まない
+    pub fn set_count(&mut self, value: i32) {
+        let value = self.clamped(value);
+        if value == self.count {
+            return;
+        }
+        self.record();
+        self.count = value;
+        self.update_dirty();
+    }
+
+    // スライダーのドラッグ中の値をcountに設定する．ドラッグ中は履歴に積まない
+    fn drag_to(&mut self, value: i32) {
+        if self.drag_origin.is_none() {
+            self.drag_origin = Some(self.count);
+        }
+        self.count = self.clamped(value);
+    }
+
+    // ドラッグを終え，ドラッグを始める前の値を1つの変更として履歴に積む
+    // 元の値に戻して離した場合は変更ではないため積まない
+    fn end_drag(&mut self) {
+        if let Some(origin) = self.drag_origin.take() {
+            if origin != self.count {
+                self.history.push(origin);
+                self.redo.clear();
+            }
+        }
+    }
+
+    fn update_dirty(&mut self) {
+        self.dirty = self.count != self.initial;
+    }
+
+    // 符号付きの変化量をcountに1回の操作として適用する
+    // i32の範囲を超える場合はoverflow_policyに従う
+    fn add(&mut self, delta: i32) {
+        if let Some(value) = self.overflow_policy.add(self.count, delta) {
+            self.set_count(value);
+        }
+    }
+
+    // 溜まっているAddの変化量を届いた順に適用し，結果を1つの変更としてcountに設定する
+    // 1つずつ適用した場合と同じ値になるように，変化量ごとにoverflow_policyと範囲を適用する
+    // 変化量を1つでも適用した場合はtrueを返す
+    fn apply_pending(&mut self) -> bool {
+        let mut value = self.count;
+        let mut applied = false;
+        for delta in std::mem::take(&mut self.pending) {
+            if let Some(next) = self.overflow_policy.add(value, delta) {
+                value = self.clamped(next);
+                applied = true;
+            }
+        }
+        if applied {
+            self.set_count(value);
+        }
+        applied
+    }
+
+    // "+10をまとめて"のような操作を，stepごとではなく1つの変更として適用する
+    // 履歴には全体で1つだけ積まれるため，Undoを1回行うと適用する前の値に戻る
+    pub fn apply_batch(&mut self, delta: i32) {
+        self.add(delta);
+    }
+
+    // 符号を反転すると範囲を超える場合があるため，減少はaddを使わずに計算する
+    fn subtract(&mut self, amount: i32) {
+        if let Some(value) = self.overflow_policy.sub(self.count, amount) {
+            self.set_count(value);
+        }
+    }
+
+    // countsのすべての要素を設定する．各値は区分ごとの範囲に収める
+    // 要素数が合わない場合はどの区分の値か分からないため，一部だけ設定することはせずにすべて無視する
+    fn set_all(&mut self, values: &[i32]) {
+        if values.len() != self.counts.len() {
+            eprintln!(
+                "Ignoring SetAll with {} values for {} counters",
+                values.len(),
+                self.counts.len()
+            );
+            return;
+        }
+        let (min, max) = GUEST_RANGE;
+        for (count, value) in self.counts.iter_mut().zip(values) {
+            *count = (*value).clamp(min, max);
+        }
+    }
+
+    // countsのindex番目の要素をamountだけ増減させる．i32の範囲を超える場合はoverflow_policyに従う
+    // Counterのボタンからは範囲内の値だけが届くが，他から送られた場合も区分ごとの範囲に収める
+    fn step_at(&mut self, index: usize, direction: StepDirection, amount: i32) {
+        let policy = self.overflow_policy;
+        let (min, max) = GUEST_RANGE;
+        if let Some(count) = self.counts.get_mut(index) {
+            let value = match direction {
+                StepDirection::Increment => policy.add(*count, amount),
+                StepDirection::Decrement => policy.sub(*count, amount),
+            };
+            if let Some(value) = value {
+                *count = value.clamp(min, max);
+            }
+        }
+    }
 }
 
 impl Model for AppData {
@@ -14,136 +580,6049 @@ impl Model for AppData {
     fn event(&mut self, cx: &mut EventContext, event: &mut Event) {
         // イベントで map() を呼び出すと、イベントメッセージを指定されたタイプにキャストしようとし、成功した場合は提供されたクロージャを呼び出す．
         // クロージャーはメッセージタイプとメタデータを提供し，イベントの発生元やターゲットを特定したり，イベントメッセージを消費することでイベントの伝搬を防止することができる
-        event.map(|app_event, meta| match app_event {
-            AppEvent::Decrement => {
-                self.count -= 1;
+        // 保存するのはSavedDataに含まれるcount・weight・countsだけのため，それらを変更したときだけchangedを設定する
+        // テーマの切り替えのように保存しない値だけを変更するイベントでは，ファイルに書き込まない
+        let mut changed = false;
+        event.map(|app_event, meta| {
+            if let Some(hook) = &self.on_event {
+                (hook)(app_event);
             }
-            AppEvent::Increment => {
-                self.count += 1;
+            // キーボードでスライダーを操作した場合などはEndDragが届かないため，
+            // ドラッグ以外のイベントが届いた時点でもドラッグを終えたものとして履歴に積む
+            // ドラッグ中の値はDragValueのたびに保存しているため，ここでは保存しない
+            if !matches!(app_event, AppEvent::DragValue(_)) {
+                self.end_drag();
             }
-        })
+            // Add以外のイベントは，それより前に届いて溜まっているAddを適用してから処理する
+            // 例えばAddの直後にResetが届いた場合，後から溜まった変化量が適用されてリセットが失われないようにする
+            // 溜まっていた変化量を適用したときだけ保存する
+            if !matches!(app_event, AppEvent::Add(_)) {
+                changed = self.apply_pending();
+            }
+            match app_event {
+                // 押し続けたときの繰り返しなどで同じ処理の間に続けて届いたAddは，すぐには適用せずに溜めておく
+                // 最初のAddでApplyPendingを送ると，既にキューにあるAddがすべて処理された後に届くため，
+                // それまでに溜まった変化量をまとめて1回の変更としてcountに適用できる
+                // Labelのバインディングはcountが変わるたびに更新されるため，表示が途中の値に遅れることがなくなる
+                // countはまだ変わっていないため，保存はApplyPendingで適用したときの1回だけとなる
+                AppEvent::Add(delta) => {
+                    self.pending.push(*delta);
+                    if self.pending.len() == 1 {
+                        cx.emit(AppEvent::ApplyPending);
+                    }
+                }
+                // 溜まっている変化量はmatchの前で適用している
+                // 何も溜まっていなかった場合はchangedを設定しないため，保存もしない
+                AppEvent::ApplyPending => {}
+                AppEvent::Decrement(amount) => {
+                    self.subtract(*amount);
+                    changed = true;
+                }
+                AppEvent::Increment(amount) => {
+                    self.add(*amount);
+                    changed = true;
+                }
+                AppEvent::Reset => {
+                    self.set_count(0);
+                    changed = true;
+                }
+                AppEvent::SetValue(value) => {
+                    self.set_count(*value);
+                    changed = true;
+                }
+                AppEvent::DragValue(value) => {
+                    self.drag_to(*value);
+                    changed = true;
+                }
+                // ドラッグはmatchの前で終えている．履歴に積むだけで保存する値は変わらない
+                AppEvent::EndDrag => {}
+                // 履歴に積んだ後でSetRangeによって範囲が狭められた場合もあるため，戻す値は現在の範囲に収める
+                AppEvent::Undo => {
+                    if let Some(previous) = self.history.pop() {
+                        self.redo.push(self.count);
+                        self.count = self.clamped(previous);
+                        changed = true;
+                    }
+                }
+                AppEvent::Redo => {
+                    if let Some(next) = self.redo.pop() {
+                        self.history.push(self.count);
+                        self.count = self.clamped(next);
+                        changed = true;
+                    }
+                }
+                AppEvent::IncrementAt(index, amount) => {
+                    self.step_at(*index, StepDirection::Increment, *amount);
+                    changed = true;
+                }
+                AppEvent::DecrementAt(index, amount) => {
+                    self.step_at(*index, StepDirection::Decrement, *amount);
+                    changed = true;
+                }
+                // SetAllと同じく区分ごとの範囲に収める
+                AppEvent::SetAt(index, value) => {
+                    let (min, max) = GUEST_RANGE;
+                    if let Some(count) = self.counts.get_mut(*index) {
+                        *count = (*value).clamp(min, max);
+                        changed = true;
+                    }
+                }
+                AppEvent::SetAll(values) => {
+                    self.set_all(values);
+                    changed = true;
+                }
+                AppEvent::Step(id, direction, amount) => {
+                    if let Some(&index) = self.slots.get(id) {
+                        self.step_at(index, *direction, *amount);
+                        changed = true;
+                    }
+                }
+                // Spinnerは範囲内で飽和させてから送るが，他から送られた場合も範囲を超えないようにする
+                // u32は0を下回ると溢れるため，先に飽和させてから範囲に収める
+                AppEvent::AddRooms(amount) => {
+                    let (min, max) = ROOMS_RANGE;
+                    self.rooms = self.rooms.saturating_add(*amount).clamp(min, max);
+                }
+                AppEvent::RemoveRooms(amount) => {
+                    let (min, max) = ROOMS_RANGE;
+                    self.rooms = self.rooms.saturating_sub(*amount).clamp(min, max);
+                }
+                AppEvent::AdjustHour(delta) => {
+                    self.hour = (self.hour + *delta).clamp(0, 23);
+                }
+                AppEvent::SetHour(hour) => {
+                    self.hour = (*hour).clamp(0, 23);
+                }
+                AppEvent::ToggleLocked => {
+                    self.locked ^= true;
+                }
+                AppEvent::ToggleTheme => {
+                    self.theme = self.theme.toggled();
+                }
+                AppEvent::ToggleDirection => {
+                    self.direction = self.direction.toggled();
+                }
+                AppEvent::OpenDisplayWindow => {
+                    self.display_window = true;
+                }
+                AppEvent::CloseDisplayWindow => {
+                    self.display_window = false;
+                }
+                AppEvent::AdjustWeight(delta) => {
+                    self.weight_scaled += to_scaled(*delta);
+                    changed = true;
+                }
+                AppEvent::SetWeight(value) => {
+                    self.weight_scaled = to_scaled(*value);
+                    changed = true;
+                }
+                AppEvent::AddStep(delta) => {
+                    // stepは常に1以上とする
+                    self.step = self.step.saturating_add(*delta).max(1);
+                }
+                AppEvent::SetRange { min, max } => {
+                    if min <= max {
+                        self.min = *min;
+                        self.max = *max;
+                        // 範囲内に収めるときだけ，元に戻せるように変更として記録する
+                        if self.clamped(self.count) != self.count {
+                            self.set_count(self.count);
+                            changed = true;
+                        }
+                    }
+                }
+                AppEvent::MarkClean => {
+                    self.initial = self.count;
+                }
+                // 書き込めた場合だけ変更されていない状態とする．失敗した場合は保存ボタンを押せるまま残す
+                AppEvent::Save => match &self.save_path {
+                    Some(path) => match self.save(path) {
+                        Ok(()) => {
+                            self.initial = self.count;
+                            self.save_pending = false;
+                        }
+                        Err(err) => eprintln!("Failed to save {}: {}", path.display(), err),
+                    },
+                    None => eprintln!("Nowhere to save the counter"),
+                },
+                // 保存自体は状態の変更ではないため，再び保存を予約しない
+                AppEvent::FlushSave => {
+                    if let Some(timer) = self.save_timer {
+                        cx.stop_timer(timer);
+                    }
+                    self.flush_save();
+                }
+            }
+        });
+        // 元に戻す・やり直す操作もcountを変更するため，イベントを処理するたびに求め直す
+        self.update_dirty();
+
+        // ウィンドウを閉じてアプリケーションが終了する前に，保存を待っている変更を書き込む
+        event.map(|window_event, _meta| {
+            if let WindowEvent::WindowClose = window_event {
+                self.flush_save();
+            }
+        });
+
+        // 変更があるたびにファイルへ保存する
+        // save_delayが設定されている場合は，タイマーを最初からやり直して保存を後に回す
+        if changed {
+            match self.save_timer {
+                Some(timer) if self.save_path.is_some() => {
+                    self.save_pending = true;
+                    cx.stop_timer(timer);
+                    cx.start_timer(timer);
+                }
+                _ => self.autosave(),
+            }
+        }
     }
 }
 
 // Viziaはイベントを使用してモデルを更新したり，ビューを表示するアクションを伝える
 // イベントは，イベントを放出するビューからツリーをたどりメインインウィンドウまで伝搬する
 // イベントには任意の方にできるメッセージが含まれており，通常列挙型を使用する
+// 変化量はCounterのstepから渡されるため，各バリアントにi32の値を持たせる
+#[derive(Debug)]
 pub enum AppEvent {
-    Increment,
-    Decrement,
+    // 符号付きの変化量をcountに加える
+    // stepがいくつであっても1回の押下につき1つのイベントで済む
+    // 続けて届いた場合はApplyPendingでまとめて適用される
+    Add(i32),
+    // 溜まっているAddの変化量をcountに適用する
+    ApplyPending,
+    // 互換性のために残しているバリアント．それぞれAdd(amount)，Add(-amount)と同じ動作をする
+    Increment(i32),
+    Decrement(i32),
+    // countを0に戻す
+    Reset,
+    // 入力された値をcountに設定する
+    SetValue(i32),
+    // スライダーのドラッグ中の値をcountに設定する．ドラッグを終えるまで履歴には積まない
+    DragValue(i32),
+    // スライダーのドラッグを終える．ドラッグ中の変化全体を1つの変更として履歴に積む
+    EndDrag,
+    // countsのindex番目の要素だけを変化させる
+    IncrementAt(usize, i32),
+    DecrementAt(usize, i32),
+    // countsのindex番目の要素に値を設定する
+    SetAt(usize, i32),
+    // プリセットの読み込みなどのために，countsのすべての要素をまとめて設定する
+    // 要素数がcountsと異なる場合は警告を出して無視する
+    SetAll(Vec<i32>),
+    // どのCounterが押されたかをCounterIdで伝える．countsのうちslotsでidに対応する要素を変化させる
+    Step(CounterId, StepDirection, i32),
+    // 部屋数を変化させる．u32は負の値を持てないため増加と減少を分ける
+    AddRooms(u32),
+    RemoveRooms(u32),
+    // 時刻を変化させる・設定する
+    AdjustHour(i32),
+    SetHour(i32),
+    // countのCounterの有効・無効を切り替える
+    ToggleLocked,
+    // 明るい配色と暗い配色を切り替える
+    ToggleTheme,
+    // 左から右と右から左の並びを切り替える
+    ToggleDirection,
+    // countを大きく表示するウィンドウを開く・閉じる
+    OpenDisplayWindow,
+    CloseDisplayWindow,
+    // 直前のcountの変更を取り消す
+    Undo,
+    // 取り消した変更をやり直す
+    Redo,
+    // weightは符号付きの変化量で更新する
+    AdjustWeight(f64),
+    SetWeight(f64),
+    // countのCounterのstepを変化させる
+    AddStep(i32),
+    // countの下限と上限を変更する．countが新しい範囲の外になる場合は範囲内に収める
+    // min > maxの範囲は不正なため無視する
+    SetRange { min: i32, max: i32 },
+    // 保存した後に呼び出し，現在のcountを変更されていない状態とする
+    MarkClean,
+    // save_pathに現在の状態を書き込み，書き込めた場合は現在のcountを変更されていない状態とする
+    Save,
+    // save_delayの待ち時間が経過したときにタイマーから送られ，保存を待っている変更を書き込む
+    FlushSave,
 }
 
 // ------------------------------------------
+// Counterが扱うことのできる数値型が満たすべきトレイトをまとめたもの
+// 加算・減算・乗算とstepの初期値(1)，倍率の変換に加え，Labelへの表示，テキスト入力の解析，イベントでの受け渡しに必要なトレイトを要求する
+// ブランケット実装により，i32やi64，u32，f64などの数値型はそのままCounterで使用できる
+pub trait CounterValue:
+    Data
+    + Copy
+    + PartialOrd
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + Mul<Output = Self>
+    + One
+    + NumCast
+    + Display
+    + FromStr
+    + Send
+    + 'static
+{
+}
+
+impl<T> CounterValue for T where
+    T: Data
+        + Copy
+        + PartialOrd
+        + Add<Output = T>
+        + Sub<Output = T>
+        + Mul<Output = T>
+        + One
+        + NumCast
+        + Display
+        + FromStr
+        + Send
+        + 'static
+{
+}
+
+// valueがminからmaxの間のどこにあるかを0.0から1.0の割合で求める
+// 範囲外の値は0.0と1.0に丸め，min >= maxの場合は常に0.0とする
+pub fn value_ratio<T: CounterValue>(value: T, min: T, max: T) -> f32 {
+    let (Some(value), Some(min), Some(max)) = (value.to_f64(), min.to_f64(), max.to_f64()) else {
+        return 0.0;
+    };
+    if min >= max {
+        return 0.0;
+    }
+    ((value - min) / (max - min)).clamp(0.0, 1.0) as f32
+}
+
+// valueをstepの倍数のうち最も近いものに丸める
+// ちょうど中間の値は大きい方に丸める(round-half-up)．負の値でも同じで，step 5では-12.5は-10になる
+// 例: step 5では12は10，13は15，step 3では4は3，-5は-6になる
+// 型の範囲に収まらない場合はvalueをそのまま返す
+pub fn snap_to_step<T: CounterValue>(value: T, step: T) -> T {
+    let (Some(v), Some(s)) = (value.to_f64(), step.to_f64()) else {
+        return value;
+    };
+    if s <= 0.0 {
+        return value;
+    }
+    let snapped = (v / s + 0.5).floor() * s;
+    <T as NumCast>::from(snapped).unwrap_or(value)
+}
+
 // countをView内に保持することもできるが，今回はステートレスなViewとする
 // 代わりにモデルにバインドするLensとボタンのイベントを処理するためのコールバックを使用する
-pub struct Counter {
+// Tは扱う数値の型で，Counter::<f64>::new(cx, lens)のように指定することもできる
+pub struct Counter<T: CounterValue> {
     // AppEventへの依存を取り除くためにコールバックを追加する
-    on_increment: Option<Box<dyn Fn(&mut EventContext)>>,
-    on_decrement: Option<Box<dyn Fn(&mut EventContext)>>,
+    // コールバックにはstepの値が渡され，どれだけ変化させるかを知ることができる
+    on_increment: Option<Box<dyn Fn(&mut EventContext, T)>>,
+    on_decrement: Option<Box<dyn Fn(&mut EventContext, T)>>,
+    // リセットは変化量を持たないためEventContextのみを受け取る
+    on_reset: Option<Box<dyn Fn(&mut EventContext)>>,
+    // リセットしたときに戻す値．Noneの場合はon_resetを呼び出し，0に戻すかどうかは呼び出し側に任せる
+    default_value: Option<T>,
+    // モデルが更新された後に新しい値を受け取る
+    on_change: Option<Box<dyn Fn(&mut EventContext, T)>>,
+    // 値が一定時間変化しなくなってから最後の値を受け取る．値が変化するたびに待ち時間をやり直す
+    on_change_debounced: Option<(Duration, Box<dyn Fn(&mut EventContext, T)>)>,
+    // on_change_debouncedの待ち時間が経過したかを確かめるためのタイマー
+    debounce_timer: Timer,
+    // on_change_debouncedを呼び出す時刻．値が変化するたびにclockの時刻から計り直す
+    debounce_deadline: Option<Instant>,
+    // リセットボタンを2回押したときだけリセットする場合の，2回目を待つ時間
+    confirm_reset: Option<Duration>,
+    // リセットボタンが1回押され，2回目を待っているかどうか
+    reset_armed: bool,
+    // 確認待ちの時間を計るためのタイマー
+    confirm_timer: Timer,
+    // 値が変化したときにLabelを一瞬強調するかどうか
+    animate_on_change: bool,
+    // 強調している時間を計るためのタイマー
+    pulse_timer: Timer,
+    // テキスト入力で確定された値を受け取る
+    on_set: Option<Box<dyn Fn(&mut EventContext, T)>>,
+    // 値が下限・上限に達したときに呼び出される
+    on_min_reached: Option<Box<dyn Fn(&mut EventContext)>>,
+    on_max_reached: Option<Box<dyn Fn(&mut EventContext)>>,
+    // このCounterの番号
+    id: CounterId,
+    // 増加・減少のどちらでも呼び出され，番号と方向，飽和させた変化量を受け取る
+    on_step: Option<Box<dyn Fn(&mut EventContext, CounterId, StepDirection, T)>>,
+    // 増加・減少をコールバックに伝える前に呼び出され，falseを返すとその変化を取り消す
+    before_step: Option<Box<dyn Fn(&mut EventContext, StepDirection, T) -> bool>>,
+    // Counterがフォーカスを得たとき・失ったときに呼び出される
+    on_focus: Option<Box<dyn Fn(&mut EventContext)>>,
+    on_blur: Option<Box<dyn Fn(&mut EventContext)>>,
+    // テキスト入力で確定した値が受け付けられなかったときに，理由を受け取る
+    on_error: Option<Box<dyn Fn(&mut EventContext, &str)>>,
+    // 下限・上限に達しているためにボタンの押下が無視されたときに呼び出される
+    on_overflow: Option<Box<dyn Fn(&mut EventContext, StepDirection)>>,
+    // 増加・減少・リセットを処理するたびに，結果の種類を受け取る
+    on_feedback: Option<Box<dyn Fn(&mut EventContext, FeedbackKind)>>,
+    // Labelに表示する文字列を値から作成する．Noneの場合はto_string()を使用する
+    format: Option<Box<dyn Fn(T) -> String>>,
+    // Labelに表示する小数点以下の桁数．Noneの場合はto_string()のまま表示する
+    // 設定されている場合は，Lensから受け取った値もこの桁数に丸めて保持する
+    precision: Option<usize>,
+    // Labelに表示する数値の桁区切りと小数点の書式
+    locale: Locale,
+    // 数値の後に付ける単位の単数形と複数形
+    unit: Option<(String, String)>,
+    // Labelに表示する負の値の表し方
+    negative_style: NegativeStyle,
+    // 1000以上の値を"1.2k"のように省略して表示するかどうか
+    abbreviate: bool,
+    // 値がこの値と等しい間は，数値の代わりに文字列を表示する
+    placeholder: Option<(T, String)>,
+    // 未設定の状態から増加させたときに設定する値
+    placeholder_base: Option<T>,
+    // 1回のボタン押下で変化させる量
+    step: T,
+    // step_lensでstepがLensにバインドされているかどうか．trueの場合はstep()による設定を無視する
+    step_bound: bool,
+    // Shiftキーを押しながら操作したときにstepに掛ける倍率．1の場合は無効
+    shift_multiplier: i32,
+    // Page Up・Page Downで変化させる量．Noneの場合はstepの10倍とする
+    page_step: Option<T>,
+    // 値の下限と上限．rangeが設定されない場合は制限しない
+    min: Option<T>,
+    max: Option<T>,
+    // Lensから受け取った最新の値．上限・下限の判定やvalue()での読み出しに使用する
+    // モデルの値が変化するとValueChangedで更新されるため，常にモデルに追従するキャッシュとして扱う
+    value: T,
+    // ボタンを押し続けたときに，次の繰り返しの時刻になったかを確かめるためのタイマー
+    repeat_timer: Timer,
+    // 次に繰り返す時刻．押していない場合はNone
+    repeat_deadline: Option<Instant>,
+    // 押し始めてから繰り返しが始まるまでの時間と，繰り返しの間隔
+    repeat_delay: Duration,
+    repeat_interval: Duration,
+    // 繰り返すたびに間隔に掛ける倍率と，間隔の最小値．Noneの場合は一定の間隔で繰り返す
+    repeat_acceleration: Option<(f32, Duration)>,
+    // 現在の繰り返しの間隔．押し始めるたびにrepeat_intervalに戻す
+    current_interval: Duration,
+    // 押し続けているボタンの方向．押していない場合はNone
+    repeating: Option<StepDirection>,
+    // 値のLabelをドラッグしたときに1step変化させるのに必要な移動量(ピクセル)
+    scrub_sensitivity: f32,
+    // ドラッグ中の基準となるx座標．ドラッグしていない場合はNone
+    scrub_anchor: Option<f32>,
+    // 押してから離すまでの間に値を変化させたかどうか．変化させずに離した場合はタップとして扱う
+    scrub_moved: bool,
+    // 長押しでリセットする場合の長押しとみなす時間．Noneの場合は長押しでリセットしない
+    long_press_reset: Option<Duration>,
+    // 長押しでリセットする場合に，押し始めたボタンの方向と時刻を保持する
+    pressed: Option<(StepDirection, Instant)>,
+    // フォーカスされているときに矢印キーで操作できるかどうか
+    keyboard: bool,
+    // フォーカスされているときにリセットするキーの組み合わせ
+    reset_shortcut: KeyChord,
+    // ポインタが上にあるときにホイールで操作できるかどうか
+    wheel: bool,
+    // 右クリックでコンテキストメニューを開けるかどうか
+    context_menu: bool,
+    // 値のLabelをタップしたときに数字の入力パッドを開くかどうか
+    numpad: bool,
+    // 値のLabelをダブルクリックしたときに，その場で編集できるようにするかどうか
+    inline_edit: bool,
+    // テキスト入力で"3+4"のような計算式を受け付けるかどうか
+    allow_expressions: bool,
+    // ボタンを押している間は変更後の値を表示するだけにし，離したときに変化させるかどうか
+    preview_on_hold: bool,
+    // 変更後の値を表示しているボタンの方向．表示していない場合はNone
+    previewing: Option<StepDirection>,
+    // フォーカスされているときに数字キーで入力中の値．入力していない場合は空
+    type_ahead: String,
+    // 最後に数字キーが押されてから確定するまでの時間を計るタイマー
+    type_ahead_timer: Timer,
+    // Counter内のテキスト入力にフォーカスがあるかどうか．その間は数字キーをテキスト入力に任せる
+    text_focused: bool,
+    // Counter内のいずれかの要素にフォーカスがあるかどうか
+    focus_within: bool,
+    // FocusOutを受け取ってから，内部の別の要素へのFocusInを待っている間true
+    blur_pending: bool,
+    // リセットしたときに読み上げる文字列．{value}はリセット後の値に置き換えられる
+    reset_announcement: String,
+    // リセットした後の値がValueChangedで届くのを待っているかどうか
+    reset_announce_pending: bool,
+    // 直近の変化量を表示する場合の変化の記録．Noneの場合は記録しない
+    rate: Option<RateTracker<T>>,
+    // 時間窓から外れた記録を取り除いて表示を更新するためのタイマー
+    rate_timer: Timer,
+    // 値が変化するたびに新しい値を送るチャネル
+    subscribers: Vec<ChangeSender<T>>,
+    // 値の範囲ごとにCounterへ付与するクラス．下限の小さい順に並べて保持する
+    thresholds: Vec<(T, String)>,
+    // 目標の値．値がこれ以上の間はreachedクラスを付与する
+    target: Option<T>,
+    // 押下を受け付けてから次の押下を受け付けるまでの時間．Noneの場合は制限しない
+    cooldown: Option<Duration>,
+    // 最後に押下を受け付けた時刻
+    last_press: Option<Instant>,
+    // cooldownや長押し，繰り返しやdebounceの時刻の判定に使う時計
+    clock: Rc<dyn Clock>,
+    // 1行分に満たないホイールの移動量
+    wheel_delta: f32,
+    // trueの間はボタンや入力による変更を受け付けない
+    disabled: bool,
+    // 増加・減少をそれぞれ受け付けるかどうか
+    increment_enabled: bool,
+    decrement_enabled: bool,
+    // 増加・減少ボタンの中身を作る関数．Noneの場合はbutton_labelsの文字列を表示する
+    // ボタンを作り直すたびに呼び出すため，Rcで共有する
+    increment_content: Option<Rc<dyn Fn(&mut Context)>>,
+    decrement_content: Option<Rc<dyn Fn(&mut Context)>>,
+    // 値のLabelの前に表示するアイコンを作る関数．Noneの場合は表示しない
+    leading_icon: Option<Rc<dyn Fn(&mut Context)>>,
+    // テキスト入力で確定した値をstepの倍数に丸めるかどうか
+    snap: bool,
+    // 上限・下限に達した状態でさらに変化させたときに，反対側の端へ戻るかどうか
+    wrap: bool,
+    // テキスト入力をどの操作で確定するか
+    commit_mode: CommitMode,
 }
 
-// ユーザーがコールバックを追加できるようにするためにCounterにCounterModifiersトレイトを定義する
-pub trait CounterModifiers {
-    // 'staticライフタイムはコールバックがプログラム全体で有効であることを示す
-    // callbackはEventContextを受け取り，何らかのアクションを実行する
-    fn on_increment<F: Fn(&mut EventContext) + 'static>(self, callback: F) -> Self;
-    fn on_decrement<F: Fn(&mut EventContext) + 'static>(self, callback: F) -> Self;
+// テキスト入力の値を確定する操作
+// 確定しなかった入力は現在の値に戻す
+#[derive(Clone, Copy, PartialEq)]
+pub enum CommitMode {
+    // Enterが押されたときだけ確定する
+    OnEnter,
+    // フォーカスが外れたときだけ確定する
+    OnBlur,
+    // どちらでも確定する
+    OnEnterOrBlur,
 }
 
-// CounterModifiersトレイトをHandle<'a, Counter>に実装する
-// 'aはHandleのライフタイムパラメータで，Counterのライフタイムを指定する．
-// HandleはViewを操作するためのハンドルで，Viewの状態を変更するためのメソッドを提供する
-impl<'a> CounterModifiers for Handle<'a, Counter> {
-    // Handleのmodifyメソッドを使用することで直接callbackを追加することができる
-    fn on_decrement<F: Fn(&mut EventContext) + 'static>(self, callback: F) -> Self {
-        self.modify(|counter| counter.on_decrement = Some(Box::new(callback)))
-    }
-    fn on_increment<F: Fn(&mut EventContext) + 'static>(self, callback: F) -> Self {
-        self.modify(|counter| counter.on_increment = Some(Box::new(callback)))
+// Counterを区別するための番号
+// 同じイベントを送る複数のCounterがあっても，モデル側でどのCounterから送られたかを判別できる
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct CounterId(pub u64);
+
+// Counter::newで自動的に割り当てる番号
+// new_with_idで0から順に番号を指定しても重ならないように，2^32から始める
+static NEXT_COUNTER_ID: AtomicU64 = AtomicU64::new(1 << 32);
+
+impl CounterId {
+    // 前回とは異なる番号を割り当てる
+    fn next() -> Self {
+        CounterId(NEXT_COUNTER_ID.fetch_add(1, Ordering::Relaxed))
     }
 }
 
-// ボタンから発行するイベントを作成する
-pub enum CounterEvent {
+// 押し続けているボタンがどちらの方向に変化させるかを表す
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum StepDirection {
     Increment,
     Decrement,
 }
 
-// View traitを実装することでビューを定義する
-impl View for Counter {
-    // 動的に追加されるコールバックをイベントによって呼び出す
-    fn event(&mut self, cx: &mut EventContext, event: &mut Event) {
-        event.map(|e, _meta| match e {
-            CounterEvent::Increment => {
-                if let Some(callback) = &self.on_increment {
-                    (callback)(cx);
-                }
-            }
-            CounterEvent::Decrement => {
-                if let Some(callback) = &self.on_decrement {
-                    (callback)(cx);
-                }
-            }
-        });
-    }
+// on_feedbackに渡される，操作の結果の種類
+// 音や振動など，種類に応じた効果を埋め込む側で鳴らすために使う
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum FeedbackKind {
+    // stepの分だけ変化した
+    Step,
+    // 下限・上限で飽和したか，既に下限・上限に達していた
+    Clamp,
+    // リセットした
+    Reset,
 }
 
-impl Counter {
-    // Viewを使用するにはコンストラクタでViewをContextに追加する必要がある
-    // データバインディングを追加するにはコンストラクタでLensを引数に渡す必要がある．またLensはジェネリックを使用してLens traitを実装する任意の型を受け取っている
-    pub fn new<L>(cx: &mut Context, lens: L) -> Handle<Self>
+// ユーザーがコールバックを追加できるようにするためにCounterにCounterModifiersトレイトを定義する
+pub trait CounterModifiers {
+    // Counterが扱う数値の型
+    type Value;
+
+    // 'staticライフタイムはコールバックがプログラム全体で有効であることを示す
+    // callbackはEventContextとstepの値を受け取り，何らかのアクションを実行する
+    fn on_increment<F: Fn(&mut EventContext, Self::Value) + 'static>(self, callback: F) -> Self;
+    fn on_decrement<F: Fn(&mut EventContext, Self::Value) + 'static>(self, callback: F) -> Self;
+    fn on_reset<F: Fn(&mut EventContext) + 'static>(self, callback: F) -> Self;
+    // 値が変化したときに変化後の値を受け取る
+    fn on_change<F: Fn(&mut EventContext, Self::Value) + 'static>(self, callback: F) -> Self;
+    // 値がdelayの間変化しなくなったときに，最後の値を1回だけ受け取る
+    // 押し続けやドラッグで値が連続して変化する間は呼び出されないため，ファイルや通信などの重い処理に使う
+    fn on_change_debounced<F: Fn(&mut EventContext, Self::Value) + 'static>(
+        self,
+        delay: Duration,
+        callback: F,
+    ) -> Self;
+    // テキスト入力でEnterが押され，値が確定したときに呼び出される
+    fn on_set<F: Fn(&mut EventContext, Self::Value) + 'static>(self, callback: F) -> Self;
+    // rangeで設定した下限・上限に値が達したときに呼び出される
+    // 達した状態でさらにボタンが押されても再度呼び出されることはない
+    fn on_min_reached<F: Fn(&mut EventContext) + 'static>(self, callback: F) -> Self;
+    fn on_max_reached<F: Fn(&mut EventContext) + 'static>(self, callback: F) -> Self;
+    // 増加・減少のどちらでも呼び出され，Counterの番号と方向，変化量を受け取る
+    // 複数のCounterで同じコールバックを使い，モデル側で番号によって変化させる値を選ぶために使う
+    fn on_step<F: Fn(&mut EventContext, CounterId, StepDirection, Self::Value) + 'static>(
+        self,
+        callback: F,
+    ) -> Self;
+    // 増加・減少の前に方向と飽和させた変化量を受け取り，falseを返すとその変化を取り消す
+    // 取り消した場合はon_incrementなどのコールバックは呼び出されず，モデルは変更されない
+    // 外部の状態や業務上の規則で変更を拒否するために使う．変化後の値はCounterのLensの値に変化量を加えて求める
+    // 符号付きの変化量を1つ渡す代わりに方向と大きさに分けて渡すのは，u32のような符号なしの型では
+    // 減少を負の値として表せないため．i32のCounterでの-amountは，Decrementとamountで表される
+    fn before_step<F: Fn(&mut EventContext, StepDirection, Self::Value) -> bool + 'static>(
+        self,
+        callback: F,
+    ) -> Self;
+    // Counterがフォーカスを得たとき・失ったときに呼び出される
+    // Tabキーでフォーカスが移動した場合も呼び出されるため，フォームの入力欄を離れたときの検証に使える
+    fn on_focus<F: Fn(&mut EventContext) + 'static>(self, callback: F) -> Self;
+    fn on_blur<F: Fn(&mut EventContext) + 'static>(self, callback: F) -> Self;
+    // テキスト入力に数値でない文字列や範囲外の値が確定されたときに，画面に表示するものと同じ理由を受け取る
+    fn on_error<F: Fn(&mut EventContext, &str) + 'static>(self, callback: F) -> Self;
+    // 下限・上限に達した状態でさらに押されたときに，押されるたびに呼び出される
+    // 押し続けている間の繰り返しでも1回ごとに呼び出される．引数は押されたボタンの方向
+    fn on_overflow<F: Fn(&mut EventContext, StepDirection) + 'static>(self, callback: F) -> Self;
+    // 増加・減少・リセットのたびに結果の種類を受け取る関数を設定する
+    // クリック音や振動はこのクレートでは鳴らさず，種類に応じた効果を呼び出し側で再生する
+    fn on_feedback<F: Fn(&mut EventContext, FeedbackKind) + 'static>(self, callback: F) -> Self;
+    // Labelに表示する文字列を整形する関数を設定する．モデルの値は変更しない
+    fn format<F: Fn(Self::Value) -> String + 'static>(self, formatter: F) -> Self;
+    // Labelに表示する小数点以下の桁数を設定する．例えば2桁では3.14159は"3.14"と表示される
+    // 浮動小数点数のCounterでのみ意味を持ち，整数では無視される．formatを設定した場合はformatが優先される
+    fn precision(self, digits: usize) -> Self;
+    // Labelに表示する数値を地域ごとの書式で桁区切りする．設定しない場合は区切らない
+    // テキスト入力やモデルの値は変更しない．formatを設定した場合はformatが優先される
+    fn locale(self, locale: Locale) -> Self;
+    // Labelの数値の後に単位を付けて"1 item"，"2 items"のように表示する
+    // 値が1のときだけsingularを使い，0を含むそれ以外の値ではpluralを使う．formatを設定した場合はformatが優先される
+    fn unit(self, singular: &str, plural: &str) -> Self;
+    // Labelに表示する負の値の表し方を設定する．Parenthesesでは-5は"(5)"と表示される
+    // 設定しない場合はMinusとなる．formatを設定した場合はformatが優先される
+    fn negative_style(self, style: NegativeStyle) -> Self;
+    // 有効にすると，絶対値が1000以上の値をk・M・G・Tの接尾辞で省略して"1.2k"や"3.4M"のように表示する
+    // 小数点以下1桁に丸めて表示するだけで，モデルの値やテキスト入力の内容は変更しない
+    // 省略した場合はprecisionの桁数は使われない．formatを設定した場合はformatが優先される
+    fn abbreviate(self, enabled: bool) -> Self;
+    // 値がsentinelと等しい間は，値が未設定であるとして数値の代わりにtextを表示する
+    // 未設定の状態で増加ボタンを押すと，sentinelに1stepを加えるのではなくplaceholder_baseの値をon_setで設定する
+    fn placeholder(self, sentinel: Self::Value, text: &str) -> Self;
+    // 未設定の状態から増加させたときに設定する値．設定しない場合は下限，下限もない場合は0となる
+    fn placeholder_base(self, base: Self::Value) -> Self;
+    // 1回のボタン押下で変化させる量を設定する
+    fn step(self, amount: Self::Value) -> Self;
+    // 1回のボタン押下で変化させる量をLensにバインドする．step()による固定の値より優先される
+    // 押した時点のLensの値を使用するため，他のビューからstepを変更できる
+    fn step_lens<L: Lens<Target = Self::Value>>(self, lens: L) -> Self;
+    // Shiftキーを押しながら操作したときにstepを何倍にするかを設定する
+    fn shift_multiplier(self, factor: i32) -> Self;
+    // フォーカスされているときにPage Up・Page Downで変化させる量を設定する．設定しない場合はstepの10倍となる
+    fn page_step(self, amount: Self::Value) -> Self;
+    // 値の下限と上限を設定する．値はこの範囲を超えないように飽和する
+    fn range(self, min: Self::Value, max: Self::Value) -> Self;
+    // 値の下限と上限をLensにバインドする．range()による固定の範囲より優先される
+    // AppEvent::SetRangeのようにモデルの範囲を変更した場合も，Counterの範囲が合わせて変わる
+    fn range_lens<Min, Max>(self, min: Min, max: Max) -> Self
     where
-        L: Lens<Target = i32>,
-    {
-        // Viewトレイトによって提供される build()関数は、カスタムViewのコンテンツを構築するために使用できるクロージャを引数に取る。
-        Self {
-            // エラーになるため,初期化時にコールバックをNoneに設定する
-            on_increment: None,
-            on_decrement: None,
-        }
-        .build(cx, |cx| {
-            // アプリケーションにビューを追加する
-            // ビューの構成はHStackのようなコンテナビューを使って行う
-            // HStackは水平方向にビューを並べる
-            // デフォルトではHStackは親ビュー(window)を埋めるように拡張される
-            // レイアウトシステムについてはmorphormのドキュメントを参照
-            HStack::new(cx, |cx| {
-                // ボタンを追加する
-                Button::new(cx, |cx| Label::new(cx, "Decrement"))
-                    // EventContextを使用してイベントを発行する
-                    // ツリーを辿ってAppDataモデルに伝搬される
-                    .on_press(|ex| ex.emit(CounterEvent::Decrement))
-                    .class("dec");
-                Button::new(cx, |cx| Label::new(cx, "Increment"))
-                    .on_press(|ex| ex.emit(CounterEvent::Increment))
-                    .class("inc");
-                // countが更新されるたび，ビューを更新するバインディングが設定される
-                Label::new(cx, lens).class("count");
-            })
-            .class("row");
-        })
-    }
+        Min: Lens<Target = Self::Value>,
+        Max: Lens<Target = Self::Value>;
+    // リセットしたときに0ではなくvalueに戻す．例えば人数の既定値を2人にする場合に使う
+    // 設定した場合はon_resetの代わりにon_setでvalueを伝える．0に戻すリセットはvalueが0の場合と同じになる
+    fn default_value(self, value: Self::Value) -> Self;
+    // ボタンを押し続けたときに繰り返しが始まるまでの時間と繰り返しの間隔を設定する
+    fn repeat(self, initial_delay: Duration, interval: Duration) -> Self;
+    // 押し続けている間，繰り返すたびに間隔をfactor倍に縮め，min_intervalまで速くする
+    // factorは0より大きく1より小さい値とし，ボタンを離すと元の間隔に戻る
+    fn repeat_acceleration(self, factor: f32, min_interval: Duration) -> Self;
+    // ボタンをthreshold以上押し続けてから離したときに，変化させる代わりにリセットする
+    // 有効にした場合，押し続けたときの繰り返しは行わず，離したときに1回だけ変化させる
+    fn long_press_reset(self, enabled: bool, threshold: Duration) -> Self;
+    // 値のLabelを横にドラッグしたときに，何ピクセルごとに1step変化させるかを設定する
+    // 右にドラッグすると増加し，左にドラッグすると減少する
+    fn scrub_sensitivity(self, pixels_per_step: f32) -> Self;
+    // 矢印キー・Page Up・Page Down・Home・Endによる操作を有効または無効にする
+    fn keyboard(self, enabled: bool) -> Self;
+    // 値が変化するたびに，Labelの背景色を一瞬変えて変化を目立たせる
+    // 色の変化はCSSのtransitionで行うため，操作を妨げることはない
+    fn animate_on_change(self, enabled: bool) -> Self;
+    // 右クリックで"Reset"，"Set to max"，"Set to min"を選べるメニューを開けるようにする
+    // 下限・上限はrangeで設定された値を使用し，設定されていない場合は何もしない
+    fn context_menu(self, enabled: bool) -> Self;
+    // 有効にすると，値のLabelをドラッグせずにタップしたときに数字の入力パッドを開く
+    // タッチ操作の端末でキーボードを使わずに値を直接入力するために使う
+    // OKではテキスト入力と同じように範囲を検証してon_setに伝え，Cancelでは値を変更せずに閉じる
+    fn numpad(self, enabled: bool) -> Self;
+    // 有効にすると，値のLabelをダブルクリックしたときにその場でテキスト入力に切り替えて値を編集できる
+    // Enterかフォーカスが外れたときにテキスト入力と同じ検証をして確定し，Escapeでは値を変更せずにLabelに戻す
+    fn inline_edit(self, enabled: bool) -> Self;
+    // 有効にすると，テキスト入力で"3+4"や"(2+3)*10"のような整数の計算式を受け付け，計算した結果を設定する
+    // 式として解釈できない場合は数値でない場合と同じようにエラーを表示する
+    // 数値としてそのまま解析できる入力は式として扱わないため，浮動小数点数のCounterでも"3.5"は受け付けられる
+    // ただし式は整数だけを扱うため，"1.5*2"のような小数を含む式は受け付けない
+    fn allow_expressions(self, enabled: bool) -> Self;
+    // 有効にすると，ボタンを押している間は変更後の値をpreviewクラスの付いたLabelに表示するだけにする
+    // ボタンの上で離したときに初めてon_incrementなどを呼び出し，離す前にポインタが外れた場合は取り消す
+    // 取り消しにくい操作や，変更するたびに重い処理が走る値に使う．押し続けても繰り返しはしない
+    fn preview_on_hold(self, enabled: bool) -> Self;
+    // 直近のwindowの間に値がどれだけ変化したかを"+3 in the last 5s"のように表示する
+    // 時間窓から外れた変化は取り除かれるため，操作しないでいると表示は0に戻る
+    fn show_rate(self, window: Duration) -> Self;
+    // リセットしたときにスクリーンリーダーが読み上げる文字列を設定する．既定は"Reset to {value}"
+    // {value}はリセット後の値をLabelと同じ書式で整形したものに置き換えられる
+    fn reset_announcement(self, template: &str) -> Self;
+    // 押下を受け付けてからdurationの間は次の押下を無視し，素早いダブルクリックで2回変化しないようにする
+    // 押し続けたときの繰り返しは意図した操作のため，この制限を受けない
+    fn cooldown(self, duration: Duration) -> Self;
+    // cooldownや長押し，繰り返しやdebounceの判定に使う時計を差し替える．設定しない場合はシステムの時計を使う
+    // テストでは進め方を制御できる時計を渡し，実際に待たずに時間の経過を再現する
+    fn clock(self, clock: Rc<dyn Clock>) -> Self;
+    // 値の範囲に応じてCounterにクラスを付与する．rulesは(下限, クラス名)の組で，
+    // 値以下の下限のうち最も大きいものの1つだけが付与される．CSSから`counter.クラス名 label.value`で色を変えられる
+    fn thresholds(self, rules: Vec<(Self::Value, &str)>) -> Self;
+    // 目標の値を設定する．値がvalue以上の間はCounterにreachedクラスを付与し，CSSで色を変えられるようにする
+    // 目標を下回るとクラスは外れる．他のビューを目標に合わせて変える場合はCounter::reached_lensを使う
+    fn target(self, value: Self::Value) -> Self;
+    // 値が変化するたびに新しい値をsenderに送る．subscription::channelで作った受信側で順に受け取れる
+    // 送信はUIスレッドで行われるが待つことはなく，受信側が遅い場合は古い値から捨てられる
+    // 受信側が破棄されると送信をやめる
+    fn subscribe(self, sender: ChangeSender<Self::Value>) -> Self;
+    // 有効にすると，リセットボタンを1回押しただけではリセットせず"Sure?"と表示する
+    // timeout以内にもう一度押したときだけリセットし，押されなかった場合は元の表示に戻す
+    fn confirm_reset(self, enabled: bool, timeout: Duration) -> Self;
+    // フォーカスされているときにリセットするキーの組み合わせを設定する．設定しない場合はCtrl+0となる
+    fn reset_shortcut(self, chord: KeyChord) -> Self;
+    // ポインタが上にあるときにホイールを回すとstepずつ変化させる
+    fn wheel(self, enabled: bool) -> Self;
+    // テキスト入力で確定した値をstepの倍数のうち最も近いものに丸める
+    // ドラッグによる変更は常にstep単位で行われる
+    // 下限・上限への移動や反対側の端への移動，default_valueへのリセットでは丸めず，その値をそのまま設定する
+    fn snap(self, enabled: bool) -> Self;
+    // 有効にすると，上限に達した状態で増加させたときに下限へ，下限で減少させたときに上限へ戻る
+    // rangeで上限と下限の両方を設定した場合にのみ意味を持つ
+    // stepが残りの幅より大きい場合は，従来通りまず上限・下限で止まり，次の操作で反対側の端へ移る
+    // 例えば0から23でstepが5の場合，20 -> 23 -> 0 -> 5のように変化する
+    fn wrap(self, enabled: bool) -> Self;
+    // テキスト入力をEnterで確定するか，フォーカスが外れたときに確定するかを設定する
+    // 設定しない場合はどちらでも確定する
+    fn commit_mode(self, mode: CommitMode) -> Self;
+    // Lensの値がtrueの間，Counter全体を無効にする
+    // viziaのStyleModifiers::disabledと名前が衝突しないようにdisabled_whenとしている
+    fn disabled_when<L: Lens<Target = bool>>(self, lens: L) -> Self;
+    // Lensの値がfalseの間，増加ボタンだけを無効にし，増加のコールバックを呼び出さない
+    // rangeによる上限・下限での飽和とは独立に働く
+    fn increment_enabled<L: Lens<Target = bool>>(self, lens: L) -> Self;
+    // Lensの値がfalseの間，減少ボタンだけを無効にする
+    fn decrement_enabled<L: Lens<Target = bool>>(self, lens: L) -> Self;
+    // 子要素を横に並べるか縦に並べるかを設定する
+    fn orientation(self, orientation: CounterOrientation) -> Self;
+    // 横に並べるときの向きを設定する．RightToLeftでは増加ボタンが左側に来る
+    // 縦に並べる場合は影響しない
+    fn direction(self, direction: LayoutDirection) -> Self;
+    // 横に並べるときの減少ボタン・値・増加ボタンの順序を設定する．設定しない場合はMinusValuePlusとなる
+    // 順序を変えても"inc"や"dec"などのクラスはそれぞれの子要素に付いたままとなる
+    // 縦に並べる場合は増加ボタン・値・減少ボタンの順に固定されるため，この設定は使われない
+    fn layout(self, layout: CounterLayout) -> Self;
+    // 値を表示するLabelのクラス名を設定する．設定しない場合は"count"となる
+    fn count_class(self, class: &str) -> Self;
+    // 値を表示するLabelにchars文字分の最小の幅を確保する
+    // 9から10のように桁数が変わったときに，Labelの幅が変わって周りのボタンが動かないようにする
+    fn value_width(self, chars: usize) -> Self;
+    // 作成したときにtargetの子要素にフォーカスし，ウィンドウを開いてすぐにキーボードで操作できるようにする
+    // フォーカスは1つの要素にしか置けないため，同じウィンドウの複数のCounterで設定した場合は最後に作られたものが優先される
+    // targetの子要素を持たない並べ方(compactなど)では何もしない
+    fn autofocus(self, target: FocusTarget) -> Self;
+    // 増加・減少ボタンに表示する文字列を設定する
+    fn button_labels(self, increment: &str, decrement: &str) -> Self;
+    // 増加・減少ボタンの中身を，文字列のLabelの代わりにcontentで作る．アイコンのボタンにする場合に使う
+    // 設定した場合はbutton_labelsの文字列は表示されない
+    fn increment_content<F: Fn(&mut Context) + 'static>(self, content: F) -> Self;
+    fn decrement_content<F: Fn(&mut Context) + 'static>(self, content: F) -> Self;
+    // いいねの数の前にハートを表示するなど，値のLabelの前に呼び出し側が作ったアイコンを表示する
+    // アイコンはポインタのイベントを受け取らないため，アイコンの上から値をドラッグしても操作の邪魔にならない
+    fn leading_icon<F: Fn(&mut Context) + 'static>(self, builder: F) -> Self;
+    // 増加・減少ボタンの背景色をRustから設定する．スタイルシートで指定した色より優先される
+    // 利用者ごとにブランドの色を変える場合など，CSSを書き換えずに色を変えたいときに使う
+    fn accent_color(self, color: Color) -> Self;
+    // 増加・減少ボタンにポインタを乗せたときに表示するツールチップを設定する
+    // キーボードでの操作方法などを伝えるために使う
+    fn tooltips(self, increment: &str, decrement: &str) -> Self;
+    // スクリーンリーダーが読み上げるCounter全体の名前を設定する
+    fn accessibility_label(self, text: &str) -> Self;
+    // このCounterだけに適用するスタイルを書けるように，Counterに"scope-{id}"のクラスを付与する
+    // idはアプリケーションの中で重ならない名前にする．例えばstyle_scope("goal")のCounterの値のLabelには
+    // `counter.scope-goal label.count { ... }` と書いたスタイルだけが適用され，他のCounterには影響しない
+    fn style_scope(self, id: &str) -> Self;
+    // Counterが最後に受け取った値をコールバックに渡す
+    // 値の正はあくまでモデルであり，ここで得られるのはLensから通知された最新の値のキャッシュである
+    // モデルを更新するイベントを送った直後は，Lensの更新が反映されるまで古い値が返る点に注意する
+    fn value<F: FnOnce(Self::Value)>(self, callback: F) -> Self;
+    // Counterの現在の状態をまとめたCounterStateをコールバックに渡す
+    fn state<F: FnOnce(CounterState<Self::Value>)>(self, callback: F) -> Self;
+    // Handleが持つContextを使って，Counterに1回分の増加・減少を行わせる
+    // ボタンを押したときと同じように判定してからon_increment・on_decrementを呼び出すため，CounterEventを扱わずに済む
+    // HandleはContextを借用しているため，cxを別に受け取らずHandleのContextを使う
+    // Handleを手放した後に別のモデルから操作する場合は，entity()で得たEntityをCounter::emit_incrementに渡す
+    fn emit_increment(&mut self);
+    fn emit_decrement(&mut self);
 }
-// ------------------------------------------
-
-fn main() {
-    // アプリケーションを初期化する
-    // クロージャ内でContextを受け取り，ビューを追加していく
-    Application::new(|cx| {
-        // buildメソッドを使用することでアプリケーションに状態を追加する
-        // これによりモデルデータがツリーに組み込まれる．今回の場合root windowに関連付けられる
-        AppData { count: 0 }.build(cx);
 
-        Counter::new(cx, AppData::count)
-            .on_increment(|cx| cx.emit(AppEvent::Increment))
-            .on_decrement(|cx| cx.emit(AppEvent::Decrement));
+// CounterModifiersトレイトをHandle<'a, Counter<T>>に実装する
+// 'aはHandleのライフタイムパラメータで，Counterのライフタイムを指定する．
+// HandleはViewを操作するためのハンドルで，Viewの状態を変更するためのメソッドを提供する
+impl<'a, T: CounterValue> CounterModifiers for Handle<'a, Counter<T>> {
+    type Value = T;
 
-        // アプリケーションにスタイルを適用する
-        cx.add_stylesheet(include_style!("src/style.css"))
+    // Handleのmodifyメソッドを使用することで直接callbackを追加することができる
+    fn on_decrement<F: Fn(&mut EventContext, T) + 'static>(self, callback: F) -> Self {
+        self.modify(|counter| counter.on_decrement = Some(Box::new(callback)))
+    }
+    fn on_increment<F: Fn(&mut EventContext, T) + 'static>(self, callback: F) -> Self {
+        self.modify(|counter| counter.on_increment = Some(Box::new(callback)))
+    }
+    fn on_reset<F: Fn(&mut EventContext) + 'static>(self, callback: F) -> Self {
+        self.modify(|counter| counter.on_reset = Some(Box::new(callback)))
+    }
+    fn on_change<F: Fn(&mut EventContext, T) + 'static>(self, callback: F) -> Self {
+        self.modify(|counter| counter.on_change = Some(Box::new(callback)))
+    }
+    fn on_change_debounced<F: Fn(&mut EventContext, T) + 'static>(
+        self,
+        delay: Duration,
+        callback: F,
+    ) -> Self {
+        self.modify(|counter| counter.on_change_debounced = Some((delay, Box::new(callback))))
+    }
+    fn on_set<F: Fn(&mut EventContext, T) + 'static>(self, callback: F) -> Self {
+        self.modify(|counter| counter.on_set = Some(Box::new(callback)))
+    }
+    fn on_min_reached<F: Fn(&mut EventContext) + 'static>(self, callback: F) -> Self {
+        self.modify(|counter| counter.on_min_reached = Some(Box::new(callback)))
+    }
+    fn on_max_reached<F: Fn(&mut EventContext) + 'static>(self, callback: F) -> Self {
+        self.modify(|counter| counter.on_max_reached = Some(Box::new(callback)))
+    }
+    fn on_step<F: Fn(&mut EventContext, CounterId, StepDirection, T) + 'static>(
+        self,
+        callback: F,
+    ) -> Self {
+        self.modify(|counter| counter.on_step = Some(Box::new(callback)))
+    }
+    fn before_step<F: Fn(&mut EventContext, StepDirection, T) -> bool + 'static>(
+        self,
+        callback: F,
+    ) -> Self {
+        self.modify(|counter| counter.before_step = Some(Box::new(callback)))
+    }
+    fn on_focus<F: Fn(&mut EventContext) + 'static>(self, callback: F) -> Self {
+        self.modify(|counter| counter.on_focus = Some(Box::new(callback)))
+    }
+    fn on_blur<F: Fn(&mut EventContext) + 'static>(self, callback: F) -> Self {
+        self.modify(|counter| counter.on_blur = Some(Box::new(callback)))
+    }
+    fn on_error<F: Fn(&mut EventContext, &str) + 'static>(self, callback: F) -> Self {
+        self.modify(|counter| counter.on_error = Some(Box::new(callback)))
+    }
+    fn on_overflow<F: Fn(&mut EventContext, StepDirection) + 'static>(self, callback: F) -> Self {
+        self.modify(|counter| counter.on_overflow = Some(Box::new(callback)))
+    }
+    fn on_feedback<F: Fn(&mut EventContext, FeedbackKind) + 'static>(self, callback: F) -> Self {
+        self.modify(|counter| counter.on_feedback = Some(Box::new(callback)))
+    }
+    fn format<F: Fn(T) -> String + 'static>(self, formatter: F) -> Self {
+        self.modify(|counter| counter.format = Some(Box::new(formatter)))
+    }
+    fn precision(self, digits: usize) -> Self {
+        self.modify(|counter| counter.precision = Some(digits))
+    }
+    fn locale(self, locale: Locale) -> Self {
+        self.modify(|counter| counter.locale = locale)
+    }
+    fn abbreviate(self, enabled: bool) -> Self {
+        self.modify(|counter| counter.abbreviate = enabled)
+    }
+    fn negative_style(self, style: NegativeStyle) -> Self {
+        self.modify(|counter| counter.negative_style = style)
+    }
+    fn unit(self, singular: &str, plural: &str) -> Self {
+        self.modify(|counter| counter.unit = Some((singular.to_owned(), plural.to_owned())))
+    }
+    fn placeholder(self, sentinel: T, text: &str) -> Self {
+        self.modify(|counter| counter.placeholder = Some((sentinel, text.to_owned())))
+    }
+    fn placeholder_base(self, base: T) -> Self {
+        self.modify(|counter| counter.placeholder_base = Some(base))
+    }
+    fn step(self, amount: T) -> Self {
+        self.modify(|counter| {
+            if !counter.step_bound {
+                counter.step = amount;
+            }
+        })
+    }
+    fn step_lens<L: Lens<Target = T>>(self, lens: L) -> Self {
+        self.bind(lens, |handle, lens| {
+            let step = lens.get(&handle);
+            handle.modify(|counter| {
+                counter.step = step;
+                counter.step_bound = true;
+            });
+        })
+    }
+    fn shift_multiplier(self, factor: i32) -> Self {
+        self.modify(|counter| counter.shift_multiplier = factor)
+    }
+    fn page_step(self, amount: T) -> Self {
+        self.modify(|counter| counter.page_step = Some(amount))
+    }
+    fn range(self, min: T, max: T) -> Self {
+        self.modify(|counter| {
+            counter.min = Some(min);
+            counter.max = Some(max);
+        })
+    }
+    fn range_lens<Min, Max>(self, min: Min, max: Max) -> Self
+    where
+        Min: Lens<Target = T>,
+        Max: Lens<Target = T>,
+    {
+        self.bind(min, |handle, min| {
+            let min = min.get(&handle);
+            update_range(handle, |counter| counter.min = Some(min));
+        })
+        .bind(max, |handle, max| {
+            let max = max.get(&handle);
+            update_range(handle, |counter| counter.max = Some(max));
+        })
+    }
+    fn default_value(self, value: T) -> Self {
+        self.modify(|counter| counter.default_value = Some(value))
+    }
+    fn repeat(self, initial_delay: Duration, interval: Duration) -> Self {
+        self.modify(|counter| {
+            counter.repeat_delay = initial_delay;
+            counter.repeat_interval = interval;
+        })
+    }
+    fn repeat_acceleration(self, factor: f32, min_interval: Duration) -> Self {
+        // 1以上では速くならず，0以下では間隔が0になるため範囲内に収める
+        let factor = factor.clamp(0.01, 1.0);
+        self.modify(|counter| counter.repeat_acceleration = Some((factor, min_interval)))
+    }
+    fn long_press_reset(self, enabled: bool, threshold: Duration) -> Self {
+        self.modify(|counter| counter.long_press_reset = enabled.then_some(threshold))
+    }
+    fn scrub_sensitivity(self, pixels_per_step: f32) -> Self {
+        // 0以下では移動量を割れないため，最低でも1ピクセルとする
+        self.modify(|counter| counter.scrub_sensitivity = pixels_per_step.max(1.0))
+    }
+    fn keyboard(self, enabled: bool) -> Self {
+        self.modify(|counter| counter.keyboard = enabled)
+    }
+    fn context_menu(self, enabled: bool) -> Self {
+        self.modify(|counter| counter.context_menu = enabled)
+    }
+    fn numpad(self, enabled: bool) -> Self {
+        self.modify(|counter| counter.numpad = enabled)
+    }
+    fn inline_edit(self, enabled: bool) -> Self {
+        self.modify(|counter| counter.inline_edit = enabled)
+    }
+    fn allow_expressions(self, enabled: bool) -> Self {
+        self.modify(|counter| counter.allow_expressions = enabled)
+    }
+    fn preview_on_hold(self, enabled: bool) -> Self {
+        self.modify(|counter| counter.preview_on_hold = enabled)
+    }
+    fn reset_announcement(self, template: &str) -> Self {
+        self.modify(|counter| counter.reset_announcement = template.to_owned())
+    }
+    fn show_rate(mut self, window: Duration) -> Self {
+        // 表示する文字列は構築時に送られたValueChangedを処理するときに作られる
+        let entity = self.entity();
+        self.context()
+            .emit_to(entity, CounterLayoutEvent::SetShowRate(true));
+        self.modify(|counter| counter.rate = Some(RateTracker::new(window)))
+    }
+    fn cooldown(self, duration: Duration) -> Self {
+        self.modify(|counter| counter.cooldown = Some(duration))
+    }
+    fn clock(self, clock: Rc<dyn Clock>) -> Self {
+        self.modify(|counter| counter.clock = clock)
+    }
+    fn thresholds(self, rules: Vec<(T, &str)>) -> Self {
+        let mut rules: Vec<(T, String)> = rules
+            .into_iter()
+            .map(|(bound, class)| (bound, class.to_owned()))
+            .collect();
+        // PartialOrdしか実装されていないため，比較できない値(NaN)は同じものとして扱う
+        rules.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(std::cmp::Ordering::Equal));
+        self.modify(|counter| counter.thresholds = rules)
+    }
+    fn target(self, value: T) -> Self {
+        self.modify(|counter| counter.target = Some(value))
+    }
+    fn subscribe(self, sender: ChangeSender<T>) -> Self {
+        self.modify(|counter| counter.subscribers.push(sender))
+    }
+    fn confirm_reset(self, enabled: bool, timeout: Duration) -> Self {
+        self.modify(|counter| counter.confirm_reset = enabled.then_some(timeout))
+    }
+    fn animate_on_change(self, enabled: bool) -> Self {
+        self.modify(|counter| counter.animate_on_change = enabled)
+    }
+    fn reset_shortcut(self, chord: KeyChord) -> Self {
+        self.modify(|counter| counter.reset_shortcut = chord)
+    }
+    fn wheel(self, enabled: bool) -> Self {
+        self.modify(|counter| counter.wheel = enabled)
+    }
+    fn snap(self, enabled: bool) -> Self {
+        self.modify(|counter| counter.snap = enabled)
+    }
+    fn wrap(self, enabled: bool) -> Self {
+        self.modify(|counter| counter.wrap = enabled)
+    }
+    fn commit_mode(self, mode: CommitMode) -> Self {
+        self.modify(|counter| counter.commit_mode = mode)
+    }
+    fn disabled_when<L: Lens<Target = bool>>(self, lens: L) -> Self {
+        self.bind(lens, |handle, lens| {
+            let disabled = lens.get(&handle);
+            // ボタンなどの子要素も操作できないようにviziaのdisabledも設定する
+            handle
+                .modify(|counter| counter.disabled = disabled)
+                .toggle_class("disabled", disabled)
+                .disabled(disabled);
+        })
+    }
+    fn increment_enabled<L: Lens<Target = bool>>(self, lens: L) -> Self {
+        self.bind(lens, |handle, lens| {
+            let enabled = lens.get(&handle);
+            set_step_enabled(handle, StepDirection::Increment, enabled);
+        })
+    }
+    fn decrement_enabled<L: Lens<Target = bool>>(self, lens: L) -> Self {
+        self.bind(lens, |handle, lens| {
+            let enabled = lens.get(&handle);
+            set_step_enabled(handle, StepDirection::Decrement, enabled);
+        })
+    }
+    fn orientation(mut self, orientation: CounterOrientation) -> Self {
+        // 並べ方はCounter内部のモデルが保持しているため，イベントで変更を伝える
+        let entity = self.entity();
+        self.context()
+            .emit_to(entity, CounterLayoutEvent::SetOrientation(orientation));
+        self
+    }
+    fn direction(mut self, direction: LayoutDirection) -> Self {
+        let entity = self.entity();
+        self.context()
+            .emit_to(entity, CounterLayoutEvent::SetDirection(direction));
+        self
+    }
+    fn layout(mut self, layout: CounterLayout) -> Self {
+        let entity = self.entity();
+        self.context()
+            .emit_to(entity, CounterLayoutEvent::SetLayout(layout));
+        self
+    }
+    fn count_class(mut self, class: &str) -> Self {
+        let entity = self.entity();
+        self.context()
+            .emit_to(entity, CounterLayoutEvent::SetCountClass(class.to_owned()));
+        self
+    }
+    fn value_width(mut self, chars: usize) -> Self {
+        let entity = self.entity();
+        let width = Units::Pixels(chars as f32 * VALUE_CHAR_WIDTH);
+        self.context()
+            .emit_to(entity, CounterLayoutEvent::SetValueWidth(width));
+        self
+    }
+    fn autofocus(mut self, target: FocusTarget) -> Self {
+        let entity = self.entity();
+        self.context()
+            .emit_to(entity, CounterLayoutEvent::SetAutofocus(target));
+        self
+    }
+    fn button_labels(mut self, increment: &str, decrement: &str) -> Self {
+        let entity = self.entity();
+        self.context().emit_to(
+            entity,
+            CounterTextEvent::SetButtonLabels(increment.to_owned(), decrement.to_owned()),
+        );
+        self
+    }
+    fn increment_content<F: Fn(&mut Context) + 'static>(self, content: F) -> Self {
+        let mut handle = self.modify(|counter| counter.increment_content = Some(Rc::new(content)));
+        let entity = handle.entity();
+        handle.context().emit_to(
+            entity,
+            CounterTextEvent::SetCustomContent(StepDirection::Increment),
+        );
+        handle
+    }
+    fn decrement_content<F: Fn(&mut Context) + 'static>(self, content: F) -> Self {
+        let mut handle = self.modify(|counter| counter.decrement_content = Some(Rc::new(content)));
+        let entity = handle.entity();
+        handle.context().emit_to(
+            entity,
+            CounterTextEvent::SetCustomContent(StepDirection::Decrement),
+        );
+        handle
+    }
+    fn leading_icon<F: Fn(&mut Context) + 'static>(self, builder: F) -> Self {
+        let mut handle = self.modify(|counter| counter.leading_icon = Some(Rc::new(builder)));
+        let entity = handle.entity();
+        handle
+            .context()
+            .emit_to(entity, CounterLayoutEvent::SetLeadingIcon(true));
+        handle
+    }
+    fn accent_color(mut self, color: Color) -> Self {
+        let entity = self.entity();
+        self.context()
+            .emit_to(entity, CounterLayoutEvent::SetAccent(color));
+        self
+    }
+    fn tooltips(mut self, increment: &str, decrement: &str) -> Self {
+        let entity = self.entity();
+        self.context().emit_to(
+            entity,
+            CounterTextEvent::SetTooltips(increment.to_owned(), decrement.to_owned()),
+        );
+        self
+    }
+    fn accessibility_label(self, text: &str) -> Self {
+        self.name(text.to_owned())
+    }
+    fn style_scope(self, id: &str) -> Self {
+        self.class(&format!("scope-{}", id))
+    }
+    fn value<F: FnOnce(T)>(self, callback: F) -> Self {
+        self.modify(|counter| callback(counter.value))
+    }
+    fn state<F: FnOnce(CounterState<T>)>(self, callback: F) -> Self {
+        self.modify(|counter| callback(counter.state()))
+    }
+    fn emit_increment(&mut self) {
+        let entity = self.entity();
+        self.context().emit_to(entity, CounterEvent::<T>::Increment);
+    }
+    fn emit_decrement(&mut self) {
+        let entity = self.entity();
+        self.context().emit_to(entity, CounterEvent::<T>::Decrement);
+    }
+}
+
+// Counterの設定をまとめたもの
+// JSONなどに保存しておき，Counter::from_configで同じ設定のCounterを作ることができる
+// 省略した項目はモディファイアを呼び出さなかった場合と同じになる
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CounterConfig<T> {
+    #[serde(default)]
+    pub step: Option<T>,
+    #[serde(default)]
+    pub min: Option<T>,
+    #[serde(default)]
+    pub max: Option<T>,
+    #[serde(default = "default_orientation")]
+    pub orientation: CounterOrientation,
+    // Counterに付与するクラス名
+    #[serde(default)]
+    pub class: Option<String>,
+}
+
+fn default_orientation() -> CounterOrientation {
+    CounterOrientation::Horizontal
+}
+
+impl<T> Default for CounterConfig<T> {
+    fn default() -> Self {
+        Self {
+            step: None,
+            min: None,
+            max: None,
+            orientation: default_orientation(),
+            class: None,
+        }
+    }
+}
+
+// ボタンごとの有効・無効をCounterとボタンの両方に反映する
+// Counterはコールバックを呼び出すかどうかの判定に，内部のモデルはボタンのdisabledに使用する
+fn set_step_enabled<T: CounterValue>(
+    mut handle: Handle<Counter<T>>,
+    direction: StepDirection,
+    enabled: bool,
+) {
+    let entity = handle.entity();
+    handle.context().emit_to(
+        entity,
+        CounterLayoutEvent::SetStepEnabled(direction, enabled),
+    );
+    handle.modify(|counter| match direction {
+        StepDirection::Increment => counter.increment_enabled = enabled,
+        StepDirection::Decrement => counter.decrement_enabled = enabled,
+    });
+}
+
+// Counterの下限・上限を変更し，上限・下限に達したときのクラスと表示を新しい範囲に合わせる
+// 値が変わらなくても判定は変わるため，同じ値のValueChangedを送って求め直させる
+fn update_range<T: CounterValue>(handle: Handle<Counter<T>>, update: impl FnOnce(&mut Counter<T>)) {
+    let mut value = None;
+    let mut handle = handle.modify(|counter| {
+        update(counter);
+        value = Some(counter.value);
+    });
+    if let Some(value) = value {
+        let entity = handle.entity();
+        handle
+            .context()
+            .emit_to(entity, CounterEvent::ValueChanged(value));
+    }
+}
+
+// Counterの状態のスナップショット
+// 表示された文字列を読み取らなくても状態を確認できるように，自動操作やテストから使用する
+// JSONとして出力できるようにSerializeを実装する
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CounterState<T> {
+    // Lensから通知された最新の値
+    pub value: T,
+    // rangeで設定された下限と上限
+    pub min: Option<T>,
+    pub max: Option<T>,
+    // disabled_whenによって無効になっているかどうか
+    pub disabled: bool,
+    // Labelに表示されている文字列
+    pub display: String,
+}
+
+// ボタンから発行するイベントを作成する
+pub enum CounterEvent<T> {
+    Increment,
+    Decrement,
+    Reset,
+    // ボタンが押されたときに送られ，1回変化させた後に繰り返しを開始する
+    StartRepeat(StepDirection),
+    // 繰り返しのタイマーから送られる
+    RepeatTick,
+    // ボタンが離されたときに送られる
+    StopRepeat,
+    // ポインタがボタンの外に出たときに送られ，押している操作を取り消す
+    CancelPress,
+    // 値のLabelをドラッグしたときに送られる．値はポインタのx座標
+    ScrubStart(f32),
+    ScrubMove(f32),
+    ScrubEnd,
+    // テキスト入力から送られ，範囲内であれば値を確定する
+    SetValue(T),
+    // テキスト入力が確定されたときに送られる．boolはEnterが押された場合にtrue，フォーカスが外れた場合にfalse
+    SubmitEntry(String, bool),
+    // テキスト入力の内容を現在の値に戻す
+    RevertEntry,
+    // Lensの値が変化したときにCounter自身に送られる
+    ValueChanged(T),
+    // on_change_debouncedの待ち時間が経過したときにタイマーから送られる
+    DebounceElapsed,
+    // リセットの確認待ちの時間が経過したときにタイマーから送られる
+    ConfirmTimeout,
+    // 値が変化したときの強調表示を終えるときにタイマーから送られる
+    PulseEnd,
+    // 直近の変化量の表示を更新するときにタイマーから送られる
+    RateTick,
+    // コンテキストメニューから送られ，値を下限・上限に設定する
+    SetToMin,
+    SetToMax,
+    // コンテキストメニューを閉じる
+    CloseMenu,
+    // 入力パッドのOKから送られ，入力された文字列を値として確定する
+    SubmitNumpad(String),
+    // 入力パッドを値を変更せずに閉じる
+    CloseNumpad,
+    // 数字キーでの入力が途切れてから一定時間が経過したときにタイマーから送られ，入力中の値を確定する
+    TypeAheadElapsed,
+    // Counter内のテキスト入力がフォーカスを得たとき・失ったときに送られる
+    TextFocus(bool),
+    // FocusOutの後にCounter自身に送られ，内部の別の要素にフォーカスが移っていなければon_blurを呼び出す
+    FocusLeft,
+    // 値のLabelをダブルクリックしたときに送られ，その場で編集するテキスト入力に切り替える
+    StartInlineEdit,
+    // その場での編集をEnterやフォーカスが外れたことで確定する
+    SubmitInline(String),
+    // その場での編集をEscapeで取り消してLabelに戻す
+    CancelInlineEdit,
+}
+
+// Counterに表示する文字列を保持するためのCounter内部のモデル
+// TextboxやLabelはLensにバインドする必要があるため，入力途中の文字列や整形済みの文字列をモデルとして持つ
+#[derive(Lens)]
+struct CounterText {
+    // テキスト入力の編集中の文字列
+    entry: String,
+    // Labelに表示する整形済みの文字列
+    display: String,
+    // 増加・減少ボタンに表示する文字列
+    increment_label: String,
+    decrement_label: String,
+    // リセットボタンに表示する文字列．確認待ちの間は"Sure?"になる
+    reset_label: String,
+    // 増加・減少ボタンのツールチップ．空の場合は表示しない
+    increment_tooltip: String,
+    decrement_tooltip: String,
+    // 増加・減少ボタンの中身をCounterに設定された関数で作るかどうか
+    increment_custom: bool,
+    decrement_custom: bool,
+    // テキスト入力で確定した値が受け付けられなかった理由．次に正しい値を確定すると消える
+    error: Option<String>,
+    // 入力パッドで入力中の文字列
+    numpad_entry: String,
+    // 直近の変化量を表す文字列
+    rate: String,
+    // リセットしたときにスクリーンリーダーが読み上げる文字列
+    announcement: String,
+}
+
+enum CounterTextEvent {
+    SetEntry(String),
+    SetDisplay(String),
+    SetButtonLabels(String, String),
+    SetResetLabel(String),
+    SetTooltips(String, String),
+    SetError(Option<String>),
+    SetCustomContent(StepDirection),
+    // 入力パッドの文字列の末尾に1文字加える・末尾の1文字を消す・空にする
+    NumpadInput(char),
+    NumpadBackspace,
+    NumpadClear,
+    SetRate(String),
+    SetAnnouncement(String),
+}
+
+// Counterの子要素の並べ方
+#[derive(Clone, Copy, PartialEq, Debug, Data, Serialize, Deserialize)]
+pub enum CounterOrientation {
+    // 従来通り横に並べる
+    Horizontal,
+    // 増加ボタンを上にして縦に並べる
+    Vertical,
+}
+
+// 横に並べるときの向き
+// アラビア語やヘブライ語のように右から左へ書く言語では，増加ボタンが左側に来るように並びを反転する
+#[derive(Clone, Copy, PartialEq, Debug, Data)]
+pub enum LayoutDirection {
+    LeftToRight,
+    RightToLeft,
+}
+
+impl LayoutDirection {
+    pub fn toggled(self) -> Self {
+        match self {
+            LayoutDirection::LeftToRight => LayoutDirection::RightToLeft,
+            LayoutDirection::RightToLeft => LayoutDirection::LeftToRight,
+        }
+    }
+}
+
+// ウィンドウを開いたときに最初にフォーカスするCounterの子要素
+#[derive(Clone, Copy, PartialEq, Data)]
+pub enum FocusTarget {
+    IncrementButton,
+    DecrementButton,
+    ValueEntry,
+}
+
+// Counterを構成する子要素
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum CounterPart {
+    Decrement,
+    Increment,
+    Value,
+    Entry,
+    Reset,
+}
+
+// 横に並べるときの減少ボタン・値・増加ボタンの順序
+// 縦に並べる場合は使われない
+#[derive(Clone, Copy, PartialEq, Debug, Data)]
+pub enum CounterLayout {
+    // 値を減少・増加ボタンで挟む．既定の並べ方
+    MinusValuePlus,
+    // 値を先に置き，右側にボタンをまとめる
+    ValueThenButtons,
+    // ボタンをまとめて先に置き，その後に値を置く
+    ButtonsThenValue,
+}
+
+// 横に並べるときの子要素を左から順に返す
+// 右から左の場合は全体を反転するため，見た目の位置が変わっても"inc"や"dec"のクラスは論理的なボタンに付いたままになる
+// compactの場合はテキスト入力とリセットボタンは作らない
+fn horizontal_parts(
+    direction: LayoutDirection,
+    compact: bool,
+    layout: CounterLayout,
+) -> Vec<CounterPart> {
+    let mut parts = match layout {
+        CounterLayout::MinusValuePlus => vec![
+            CounterPart::Decrement,
+            CounterPart::Value,
+            CounterPart::Increment,
+        ],
+        CounterLayout::ValueThenButtons => vec![
+            CounterPart::Value,
+            CounterPart::Decrement,
+            CounterPart::Increment,
+        ],
+        CounterLayout::ButtonsThenValue => vec![
+            CounterPart::Decrement,
+            CounterPart::Increment,
+            CounterPart::Value,
+        ],
+    };
+    if !compact {
+        parts.extend([CounterPart::Entry, CounterPart::Reset]);
+    }
+    if direction == LayoutDirection::RightToLeft {
+        parts.reverse();
+    }
+    parts
+}
+
+// 子要素の並べ方を保持するためのCounter内部のモデル
+// 並べ方はbuildの後にモディファイアから変更されるため，Bindingで子要素を作り直せるようにモデルとして持つ
+#[derive(Lens)]
+struct CounterLayoutState {
+    orientation: CounterOrientation,
+    // 横に並べる場合の向き
+    direction: LayoutDirection,
+    // 横に並べる場合に，値をボタンで挟んだ小さな並びにするかどうか
+    compact: bool,
+    // 横に並べる場合のボタンと値の順序
+    layout: CounterLayout,
+    // 右クリックで開くコンテキストメニューを表示しているかどうか
+    menu_open: bool,
+    // 入力パッドを表示しているかどうか
+    numpad_open: bool,
+    // 値のLabelの代わりに，その場で編集するテキスト入力を表示しているかどうか
+    editing: bool,
+    // 直近の変化量を表示するかどうか
+    show_rate: bool,
+    // 値のLabelの前にアイコンを表示するかどうか
+    leading_icon: bool,
+    // 増加・減少ボタンの背景色．Noneの場合はスタイルシートの色を使う
+    accent: Option<Color>,
+    // ボタンなどを作らず，値のLabelだけを表示するかどうか
+    display_only: bool,
+    // 増加・減少ボタンをそれぞれ操作できるかどうか
+    increment_enabled: bool,
+    decrement_enabled: bool,
+    // 値を表示するLabelに付与するクラス名
+    count_class: String,
+    // 値を表示するLabelの最小の幅．Autoの場合は表示する文字列の幅に合わせる
+    value_width: Units,
+    // 作成したときにフォーカスする子要素
+    autofocus: Option<FocusTarget>,
+}
+
+// value_widthで1文字分として確保する幅(px)．既定のフォントの数字の幅に合わせている
+const VALUE_CHAR_WIDTH: f32 = 10.0;
+
+// 数字キーでの入力が途切れてから確定するまでの時間
+const TYPE_AHEAD_DELAY: Duration = Duration::from_millis(1000);
+
+// 数字キーに対応する文字を返す．数字以外のキーの場合はNone
+fn digit_char(code: &Code) -> Option<char> {
+    let digit = match code {
+        Code::Digit0 | Code::Numpad0 => 0,
+        Code::Digit1 | Code::Numpad1 => 1,
+        Code::Digit2 | Code::Numpad2 => 2,
+        Code::Digit3 | Code::Numpad3 => 3,
+        Code::Digit4 | Code::Numpad4 => 4,
+        Code::Digit5 | Code::Numpad5 => 5,
+        Code::Digit6 | Code::Numpad6 => 6,
+        Code::Digit7 | Code::Numpad7 => 7,
+        Code::Digit8 | Code::Numpad8 => 8,
+        Code::Digit9 | Code::Numpad9 => 9,
+        _ => return None,
+    };
+    char::from_digit(digit, 10)
+}
+
+// テキスト入力などで確定された文字列を値に変換する．変換できない場合は理由を返す
+// まず数値としてそのまま解析し，解析できない場合にallow_expressionsが有効であれば整数の計算式として計算する
+fn parse_value<T: CounterValue>(text: &str, allow_expressions: bool) -> Result<T, String> {
+    if text.trim().is_empty() {
+        return Err(String::from("Enter a number"));
+    }
+    if let Ok(value) = text.parse::<T>() {
+        return Ok(value);
+    }
+    if allow_expressions {
+        let result = expression::evaluate(text)?;
+        return T::from(result).ok_or_else(|| format!("{} is out of range", result));
+    }
+    Err(format!("\"{}\" is not a number", text))
+}
+
+enum CounterLayoutEvent {
+    SetOrientation(CounterOrientation),
+    SetDirection(LayoutDirection),
+    SetCompact(bool),
+    SetLayout(CounterLayout),
+    SetMenuOpen(bool),
+    SetNumpadOpen(bool),
+    SetEditing(bool),
+    SetShowRate(bool),
+    SetLeadingIcon(bool),
+    SetAccent(Color),
+    SetDisplayOnly(bool),
+    SetStepEnabled(StepDirection, bool),
+    SetCountClass(String),
+    SetValueWidth(Units),
+    SetAutofocus(FocusTarget),
+    // autofocusでフォーカスした後に送り，Bindingで子要素が作り直されたときに再びフォーカスしないようにする
+    ClearAutofocus,
+}
+
+impl Model for CounterLayoutState {
+    fn event(&mut self, _cx: &mut EventContext, event: &mut Event) {
+        event.map(|layout_event, _meta| match layout_event {
+            CounterLayoutEvent::SetOrientation(orientation) => {
+                self.orientation = *orientation;
+            }
+            CounterLayoutEvent::SetDirection(direction) => {
+                self.direction = *direction;
+            }
+            CounterLayoutEvent::SetLayout(layout) => {
+                self.layout = *layout;
+            }
+            CounterLayoutEvent::SetCompact(compact) => {
+                self.compact = *compact;
+            }
+            CounterLayoutEvent::SetMenuOpen(open) => {
+                self.menu_open = *open;
+            }
+            CounterLayoutEvent::SetNumpadOpen(open) => {
+                self.numpad_open = *open;
+            }
+            CounterLayoutEvent::SetEditing(editing) => {
+                self.editing = *editing;
+            }
+            CounterLayoutEvent::SetShowRate(show) => {
+                self.show_rate = *show;
+            }
+            CounterLayoutEvent::SetLeadingIcon(leading_icon) => {
+                self.leading_icon = *leading_icon;
+            }
+            CounterLayoutEvent::SetAccent(color) => {
+                self.accent = Some(*color);
+            }
+            CounterLayoutEvent::SetDisplayOnly(display_only) => {
+                self.display_only = *display_only;
+            }
+            CounterLayoutEvent::SetStepEnabled(direction, enabled) => match direction {
+                StepDirection::Increment => self.increment_enabled = *enabled,
+                StepDirection::Decrement => self.decrement_enabled = *enabled,
+            },
+            CounterLayoutEvent::SetCountClass(class) => {
+                self.count_class = class.clone();
+            }
+            CounterLayoutEvent::SetValueWidth(width) => {
+                self.value_width = *width;
+            }
+            CounterLayoutEvent::SetAutofocus(target) => {
+                self.autofocus = Some(*target);
+            }
+            CounterLayoutEvent::ClearAutofocus => {
+                self.autofocus = None;
+            }
+        });
+    }
+}
+
+impl Model for CounterText {
+    fn event(&mut self, _cx: &mut EventContext, event: &mut Event) {
+        event.map(|text_event, _meta| match text_event {
+            CounterTextEvent::SetEntry(text) => {
+                self.entry = text.clone();
+            }
+            CounterTextEvent::SetDisplay(text) => {
+                self.display = text.clone();
+            }
+            CounterTextEvent::SetButtonLabels(increment, decrement) => {
+                self.increment_label = increment.clone();
+                self.decrement_label = decrement.clone();
+            }
+            CounterTextEvent::SetResetLabel(text) => {
+                self.reset_label = text.clone();
+            }
+            CounterTextEvent::SetTooltips(increment, decrement) => {
+                self.increment_tooltip = increment.clone();
+                self.decrement_tooltip = decrement.clone();
+            }
+            CounterTextEvent::SetError(error) => {
+                self.error = error.clone();
+            }
+            CounterTextEvent::NumpadInput(c) => {
+                self.numpad_entry.push(*c);
+            }
+            CounterTextEvent::NumpadBackspace => {
+                self.numpad_entry.pop();
+            }
+            CounterTextEvent::NumpadClear => {
+                self.numpad_entry.clear();
+            }
+            CounterTextEvent::SetRate(text) => {
+                self.rate = text.clone();
+            }
+            CounterTextEvent::SetAnnouncement(text) => {
+                self.announcement = text.clone();
+            }
+            CounterTextEvent::SetCustomContent(direction) => match direction {
+                StepDirection::Increment => self.increment_custom = true,
+                StepDirection::Decrement => self.decrement_custom = true,
+            },
+        });
+    }
+}
+
+impl<T: CounterValue> Counter<T> {
+    // 上限までの残りがstepより小さい場合は残りの分だけ変化させる
+    // すでに上限に達している場合はNoneを返す
+    fn increment_amount(&self, step: T) -> Option<T> {
+        match self.max {
+            Some(max) if self.value >= max => None,
+            Some(max) if max - self.value < step => Some(self.quantized(max - self.value)),
+            _ => Some(self.quantized(step)),
+        }
+    }
+
+    fn decrement_amount(&self, step: T) -> Option<T> {
+        match self.min {
+            Some(min) if self.value <= min => None,
+            Some(min) if self.value - min < step => Some(self.quantized(self.value - min)),
+            _ => Some(self.quantized(step)),
+        }
+    }
+
+    // precisionが設定されている場合は，値を10^precision倍した整数を経由して表示する桁数に丸める
+    // precision(1)では0.1を1とする整数として扱うため，浮動小数点数の足し算や引き算で生じる
+    // 0.30000000000000004のような端数が，保持する値やコールバックに渡す変化量に残らない
+    // 整数型では丸めても値は変わらない
+    fn quantized(&self, value: T) -> T {
+        self.scaled(value)
+            .and_then(|units| self.unscaled(units))
+            .unwrap_or(value)
+    }
+
+    // 値を10^precision倍して最も近い整数に丸める．precisionが設定されていない場合や変換できない場合はNone
+    fn scaled(&self, value: T) -> Option<i64> {
+        let scale = 10f64.powi(self.precision? as i32);
+        let units = (value.to_f64()? * scale).round();
+        <i64 as NumCast>::from(units)
+    }
+
+    // scaledで求めた整数を元の値に戻す
+    fn unscaled(&self, units: i64) -> Option<T> {
+        let scale = 10f64.powi(self.precision? as i32);
+        <T as NumCast>::from(units as f64 / scale)
+    }
+
+    fn at_min(&self) -> bool {
+        self.min.is_some_and(|min| self.value <= min)
+    }
+
+    fn at_max(&self) -> bool {
+        self.max.is_some_and(|max| self.value >= max)
+    }
+
+    // Shiftキーが押されている場合はstepにshift_multiplierを掛けた値を返す
+    // 倍率をTに変換できない場合は通常のstepを使用する
+    fn current_step(&self, cx: &EventContext) -> T {
+        if self.shift_multiplier != 1 && cx.modifiers().shift() {
+            if let Some(factor) = <T as NumCast>::from(self.shift_multiplier) {
+                return self.step * factor;
+            }
+        }
+        self.step
+    }
+
+    // thresholdsのうち，下限が現在の値以下である最も大きなもののクラス名を返す
+    // どの下限よりも小さい場合はNoneを返す
+    fn threshold_class(&self) -> Option<&str> {
+        self.thresholds
+            .iter()
+            .rev()
+            .find(|(bound, _)| self.value >= *bound)
+            .map(|(_, class)| class.as_str())
+    }
+
+    // cooldownが設定されている場合に，前回受け付けた押下からの時間が短い押下を無視する
+    // 受け付けた場合は時刻を記録してtrueを返す．押し続けたときの繰り返しはこの判定を通らない
+    fn accept_press(&mut self) -> bool {
+        if let (Some(cooldown), Some(last)) = (self.cooldown, self.last_press) {
+            if self.clock.now().duration_since(last) < cooldown {
+                return false;
+            }
+        }
+        self.last_press = Some(self.clock.now());
+        true
+    }
+
+    // 現在の状態のスナップショットを作る
+    pub fn state(&self) -> CounterState<T> {
+        CounterState {
+            value: self.value,
+            min: self.min,
+            max: self.max,
+            disabled: self.disabled,
+            display: self.display_text(),
+        }
+    }
+
+    // 現在の値が未設定を表す値かどうか
+    fn is_placeholder(&self) -> bool {
+        self.placeholder
+            .as_ref()
+            .is_some_and(|(sentinel, _)| self.value == *sentinel)
+    }
+
+    // 未設定の状態から増加させたときに設定する値
+    // 設定されていない場合は下限，下限もない場合は0とする
+    fn placeholder_base(&self) -> T {
+        self.placeholder_base
+            .or(self.min)
+            .unwrap_or_else(|| T::one() - T::one())
+    }
+
+    // Labelに表示する文字列を返す
+    fn display_text(&self) -> String {
+        self.text_for(self.value)
+    }
+
+    // valueをLabelに表示するときの文字列を返す
+    // 押している間の変更後の値のように，まだモデルに反映されていない値の表示にも使う
+    fn text_for(&self, value: T) -> String {
+        if let Some((_, text)) = self
+            .placeholder
+            .as_ref()
+            .filter(|(sentinel, _)| value == *sentinel)
+        {
+            return text.clone();
+        }
+        // 1000未満の値は省略せず，通常通り表示する
+        let abbreviated = value
+            .to_f64()
+            .filter(|_| self.abbreviate)
+            .and_then(|value| abbreviate_number(value, self.locale));
+        // 整数型では桁数の指定は無視されるため，そのまま表示される
+        let text = match (&self.format, abbreviated, self.precision) {
+            (Some(formatter), _, _) => return (formatter)(value),
+            (None, Some(abbreviated), _) => abbreviated,
+            (None, None, Some(digits)) => {
+                format_number(&format!("{:.*}", digits, value), self.locale)
+            }
+            (None, None, None) => format_number(&value.to_string(), self.locale),
+        };
+        let text = format_negative(&text, self.negative_style);
+        match &self.unit {
+            Some((singular, plural)) => {
+                let unit = if value == T::one() { singular } else { plural };
+                format!("{} {}", text, unit)
+            }
+            None => text,
+        }
+    }
+
+    // 方向に1回分変化させたときの値を返す．変化させられない場合はNone
+    // preview_on_holdで押している間に表示するために，step_withと同じ判定を行う
+    fn preview_value(&self, direction: StepDirection, step: T) -> Option<T> {
+        let enabled = match direction {
+            StepDirection::Increment => self.increment_enabled,
+            StepDirection::Decrement => self.decrement_enabled,
+        };
+        if !enabled {
+            return None;
+        }
+        if self.is_placeholder() {
+            return Some(self.placeholder_base()).filter(|_| direction == StepDirection::Increment);
+        }
+        match direction {
+            StepDirection::Increment => self
+                .increment_amount(step)
+                .map(|amount| self.quantized(self.value + amount)),
+            StepDirection::Decrement => self
+                .decrement_amount(step)
+                .map(|amount| self.quantized(self.value - amount)),
+        }
+        .or_else(|| self.wrapped(direction))
+    }
+
+    // 押している間に表示していた変更後の値を消し，Labelを現在の値に戻す
+    fn clear_preview(&mut self, cx: &mut EventContext) -> Option<StepDirection> {
+        let previewing = self.previewing.take();
+        if previewing.is_some() {
+            cx.toggle_class("preview", false);
+            cx.emit(CounterTextEvent::SetDisplay(self.display_text()));
+        }
+        previewing
+    }
+
+    // default_valueが設定されている場合はその値に戻し，そうでなければon_resetを呼び出す
+    // リセットした後の値はスクリーンリーダー向けに"Reset to 0"のように読み上げる
+    fn reset(&mut self, cx: &mut EventContext) {
+        // 範囲外のdefault_valueや，on_set・on_resetが設定されていない場合はリセットが伝わらない
+        let (expected, accepted) = match self.default_value {
+            Some(value) => {
+                self.set_value(cx, value);
+                (
+                    value,
+                    self.range_error(value).is_none() && self.on_set.is_some(),
+                )
+            }
+            None => {
+                if let Some(callback) = &self.on_reset {
+                    (callback)(cx);
+                }
+                (T::one() - T::one(), self.on_reset.is_some())
+            }
+        };
+        // リセットした後の値はValueChangedで届くため，届いたときに読み上げる
+        // 既にリセット後の値になっている場合はValueChangedが届かないため，すぐに読み上げる
+        // 伝わらなかった場合は値が変わらないため，読み上げも待たない
+        if !accepted {
+            self.reset_announce_pending = false;
+        } else if expected == self.value {
+            self.announce_reset(cx);
+        } else {
+            self.reset_announce_pending = true;
+        }
+        self.feedback(cx, FeedbackKind::Reset);
+    }
+
+    // reset_announcementの{value}を現在の値に置き換えて読み上げる
+    fn announce_reset(&self, cx: &mut EventContext) {
+        let text = self
+            .reset_announcement
+            .replace("{value}", &self.display_text());
+        cx.emit(CounterTextEvent::SetAnnouncement(text));
+    }
+
+    fn feedback(&self, cx: &mut EventContext, kind: FeedbackKind) {
+        if let Some(callback) = &self.on_feedback {
+            (callback)(cx, kind);
+        }
+    }
+
+    // リセットボタンや長押し，ショートカットからのリセットの要求を処理する
+    // confirm_resetが有効な場合，1回目は確認待ちにするだけで，timeout以内に再度要求されたときにリセットする
+    fn request_reset(&mut self, cx: &mut EventContext) {
+        match self.confirm_reset {
+            Some(timeout) if !self.reset_armed => {
+                self.reset_armed = true;
+                cx.emit(CounterTextEvent::SetResetLabel(String::from("Sure?")));
+                cx.modify_timer(self.confirm_timer, |timer_state| {
+                    timer_state.set_interval(timeout);
+                });
+                cx.start_timer(self.confirm_timer);
+            }
+            _ => {
+                self.disarm_reset(cx);
+                self.reset(cx);
+            }
+        }
+    }
+
+    // リセットの確認待ちを取り消し，ボタンの表示を元に戻す
+    fn disarm_reset(&mut self, cx: &mut EventContext) {
+        if self.reset_armed {
+            self.reset_armed = false;
+            cx.stop_timer(self.confirm_timer);
+            cx.emit(CounterTextEvent::SetResetLabel(String::from("Reset")));
+        }
+    }
+
+    // 値を範囲内であればon_setに伝え，表示していたエラーを消す
+    // 範囲外の値は受け付けず，理由を表示する
+    // 下限・上限やdefault_valueのように決まった値もここを通るため，stepの倍数には丸めない
+    fn set_value(&self, cx: &mut EventContext, value: T) {
+        match self.range_error(value) {
+            None => {
+                cx.emit(CounterTextEvent::SetError(None));
+                if let Some(callback) = &self.on_set {
+                    (callback)(cx, value);
+                }
+            }
+            Some(message) => self.reject_entry(cx, message),
+        }
+    }
+
+    // 値が範囲外であれば，その理由を返す
+    fn range_error(&self, value: T) -> Option<String> {
+        match (self.min, self.max) {
+            (Some(min), Some(max)) if value < min || value > max => {
+                Some(format!("Value must be between {} and {}", min, max))
+            }
+            (Some(min), None) if value < min => Some(format!("Value must be at least {}", min)),
+            (None, Some(max)) if value > max => Some(format!("Value must be at most {}", max)),
+            _ => None,
+        }
+    }
+
+    // 確定された入力を受け付けなかった理由を表示し，on_errorに伝える
+    fn reject_entry(&self, cx: &mut EventContext, message: String) {
+        if let Some(callback) = &self.on_error {
+            (callback)(cx, &message);
+        }
+        cx.emit(CounterTextEvent::SetError(Some(message)));
+    }
+
+    // 数字キーで入力された1文字を入力中の値に加え，確定するまでの時間を計り直す
+    // 入力中の値は確定するまでLabelに表示する
+    fn push_type_ahead(&mut self, cx: &mut EventContext, digit: char) {
+        self.type_ahead.push(digit);
+        cx.emit(CounterTextEvent::SetDisplay(self.type_ahead.clone()));
+        cx.toggle_class("typing", true);
+        cx.stop_timer(self.type_ahead_timer);
+        cx.start_timer(self.type_ahead_timer);
+    }
+
+    // 入力中の値をテキスト入力と同じ検証をしてから確定する
+    // 受け付けなかった場合は理由を表示し，Labelは元の値に戻す
+    fn commit_type_ahead(&mut self, cx: &mut EventContext) {
+        let text = std::mem::take(&mut self.type_ahead);
+        cx.stop_timer(self.type_ahead_timer);
+        cx.toggle_class("typing", false);
+        cx.emit(CounterTextEvent::SetDisplay(self.display_text()));
+        if !text.is_empty() {
+            self.submit_text(cx, &text);
+        }
+    }
+
+    // 入力中の値を捨て，Labelを元の値に戻す
+    fn cancel_type_ahead(&mut self, cx: &mut EventContext) {
+        self.type_ahead.clear();
+        cx.stop_timer(self.type_ahead_timer);
+        cx.toggle_class("typing", false);
+        cx.emit(CounterTextEvent::SetDisplay(self.display_text()));
+    }
+
+    // 時間窓から外れた記録を取り除き，直近の変化量の表示を更新する
+    // 記録がなくなった後はタイマーで更新する必要がないため止める
+    fn update_rate(&mut self, cx: &mut EventContext) {
+        if let Some(rate) = &mut self.rate {
+            let net = rate.net(self.clock.now());
+            cx.emit(CounterTextEvent::SetRate(rate_text(net, rate.window())));
+            if rate.is_empty() {
+                cx.stop_timer(self.rate_timer);
+            }
+        }
+    }
+
+    // 入力パッドやその場での編集で確定した文字列を値として設定する
+    // 数値として解釈でき，範囲内であればon_setに伝えてtrueを返す．そうでなければ理由を表示してfalseを返す
+    fn submit_text(&self, cx: &mut EventContext, text: &str) -> bool {
+        match self.parse_entry(text) {
+            Ok(value) => {
+                let value = self.entered(value);
+                let accepted = self.range_error(value).is_none();
+                self.set_value(cx, value);
+                accepted
+            }
+            Err(message) => {
+                self.reject_entry(cx, message);
+                false
+            }
+        }
+    }
+
+    // 入力された文字列を値に変換する．変換できない場合は理由を返す
+    fn parse_entry(&self, text: &str) -> Result<T, String> {
+        parse_value(text, self.allow_expressions)
+    }
+
+    // テキスト入力を現在の値に戻し，表示していたエラーも消す
+    fn revert_entry(&self, cx: &mut EventContext) {
+        cx.emit(CounterTextEvent::SetEntry(self.value.to_string()));
+        cx.emit(CounterTextEvent::SetError(None));
+    }
+
+    // 利用者が入力した値を，snapが有効な場合はstepの倍数に丸める
+    // テキスト入力や入力パッドなど，利用者が直接入力した値にだけ使う
+    fn entered(&self, value: T) -> T {
+        if self.snap {
+            self.snapped(value)
+        } else {
+            value
+        }
+    }
+
+    // stepの倍数に丸める
+    // 丸めた結果が範囲外になる場合は，1step内側の倍数を使う
+    // 範囲の幅がstepより狭い場合などは1step内側でも範囲外になるため，最後に下限・上限へ収める
+    fn snapped(&self, value: T) -> T {
+        let snapped = snap_to_step(value, self.step);
+        let snapped = match (self.min, self.max) {
+            (_, Some(max)) if snapped > max => snapped - self.step,
+            (Some(min), _) if snapped < min => snapped + self.step,
+            _ => snapped,
+        };
+        match (self.min, self.max) {
+            (Some(min), _) if snapped < min => min,
+            (_, Some(max)) if snapped > max => max,
+            _ => snapped,
+        }
+    }
+
+    // Lensの値をminからmaxに対する割合(0.0から1.0)に変換するLensを作る
+    // ゲージや色の濃さなど，Counterの値に応じて変化する他のビューをバインドするために使う
+    pub fn ratio_lens<L>(lens: L, min: T, max: T) -> impl Lens<Source = L::Source, Target = f32>
+    where
+        L: Lens<Target = T>,
+    {
+        lens.map(move |value| value_ratio(*value, min, max))
+    }
+
+    // wrapが有効で上限と下限の両方がある場合に，端から変化させたときに移る反対側の端を返す
+    fn wrapped(&self, direction: StepDirection) -> Option<T> {
+        if !self.wrap {
+            return None;
+        }
+        match direction {
+            StepDirection::Increment => self.min.filter(|_| self.max.is_some()),
+            StepDirection::Decrement => self.max.filter(|_| self.min.is_some()),
+        }
+    }
+
+    // Counterの外から1回分の増加・減少を行わせる
+    // ボタンを押したときと同じように，無効化やcooldown，範囲の判定をしてからon_increment・on_decrementを呼び出す
+    // 独自のイベントからCounterを操作する場合でも，CounterEventを直接扱わずに済む
+    pub fn emit_increment(cx: &mut EventContext, counter: Entity) {
+        cx.emit_to(counter, CounterEvent::<T>::Increment);
+    }
+
+    pub fn emit_decrement(cx: &mut EventContext, counter: Entity) {
+        cx.emit_to(counter, CounterEvent::<T>::Decrement);
+    }
+
+    // Lensの値がtarget以上になったかどうかを表すLensを作る
+    // 目標に達したときにチェックマークを表示するなど，Counterの外のビューをバインドするために使う
+    pub fn reached_lens<L>(lens: L, target: T) -> impl Lens<Source = L::Source, Target = bool>
+    where
+        L: Lens<Target = T>,
+    {
+        lens.map(move |value| *value >= target)
+    }
+
+    // 方向に応じて1回分の変化をコールバックに伝える
+    fn step_once(&self, cx: &mut EventContext, direction: StepDirection) {
+        let step = self.current_step(cx);
+        self.step_with(cx, direction, step);
+    }
+
+    // page_stepの分だけ変化させる．設定されていない場合はstepの10倍とする
+    fn page(&self, cx: &mut EventContext, direction: StepDirection) {
+        match (self.page_step, direction) {
+            (Some(amount), _) => self.step_with(cx, direction, amount),
+            (None, StepDirection::Increment) => self.step_times(cx, 10),
+            (None, StepDirection::Decrement) => self.step_times(cx, -10),
+        }
+    }
+
+    // stepの|steps|倍の変化を1回にまとめてコールバックに伝える．正なら増加，負なら減少させる
+    fn step_times(&self, cx: &mut EventContext, steps: i32) {
+        let direction = if steps > 0 {
+            StepDirection::Increment
+        } else {
+            StepDirection::Decrement
+        };
+        if let Some(factor) = <T as NumCast>::from(steps.abs()) {
+            self.step_with(cx, direction, self.step * factor);
+        }
+    }
+
+    // 指定した変化量を上限・下限で飽和させてコールバックに伝える
+    // 既に下限・上限に達している場合はon_overflowを呼び出す．wrapが有効な場合は代わりに反対側の端をon_setに伝える
+    // 値が未設定を表す値の場合は，増加させると基準の値を設定し，減少させても何もしない
+    fn step_with(&self, cx: &mut EventContext, direction: StepDirection, step: T) {
+        // ボタンごとに無効にされている方向は，キーボードやホイールからの操作も受け付けない
+        let enabled = match direction {
+            StepDirection::Increment => self.increment_enabled,
+            StepDirection::Decrement => self.decrement_enabled,
+        };
+        if !enabled {
+            return;
+        }
+        if self.is_placeholder() {
+            if direction == StepDirection::Increment {
+                self.set_value(cx, self.placeholder_base());
+                self.feedback(cx, FeedbackKind::Step);
+            }
+            return;
+        }
+        let (amount, callback) = match direction {
+            StepDirection::Increment => (self.increment_amount(step), &self.on_increment),
+            StepDirection::Decrement => (self.decrement_amount(step), &self.on_decrement),
+        };
+        match amount {
+            Some(amount) => {
+                if let Some(veto) = &self.before_step {
+                    if !(veto)(cx, direction, amount) {
+                        return;
+                    }
+                }
+                if let Some(callback) = callback {
+                    (callback)(cx, amount);
+                }
+                if let Some(callback) = &self.on_step {
+                    (callback)(cx, self.id, direction, amount);
+                }
+                // 下限・上限で飽和してstepより小さくなった場合はClampとする
+                if amount == self.quantized(step) {
+                    self.feedback(cx, FeedbackKind::Step);
+                } else {
+                    self.feedback(cx, FeedbackKind::Clamp);
+                }
+            }
+            None => {
+                if let Some(wrapped) = self.wrapped(direction) {
+                    self.set_value(cx, wrapped);
+                    self.feedback(cx, FeedbackKind::Step);
+                    return;
+                }
+                if let Some(callback) = &self.on_overflow {
+                    (callback)(cx, direction);
+                }
+                self.feedback(cx, FeedbackKind::Clamp);
+            }
+        }
+    }
+}
+
+// View traitを実装することでビューを定義する
+impl<T: CounterValue> View for Counter<T> {
+    // CSSから`counter`要素としてスタイルを指定できるようにする
+    fn element(&self) -> Option<&'static str> {
+        Some("counter")
+    }
+
+    // 動的に追加されるコールバックをイベントによって呼び出す
+    // コールバックには上限・下限で飽和させた変化量を渡す
+    fn event(&mut self, cx: &mut EventContext, event: &mut Event) {
+        event.map(|e: &CounterEvent<T>, meta| {
+            // CounterEventはCounterと子要素の間だけで使う内部のイベントとする
+            // 子要素から送られたイベントは親に向かって伝搬するが，必ずこのCounterで止めて祖先には届けない
+            // 無効な間に無視するイベントも含めて，処理する前に消費しておく
+            // 外側に伝えたいことはon_incrementなどのコールバックや，コールバックから送るイベントで伝える
+            meta.consume();
+
+            // 無効な間は値の変更につながるイベントを無視する
+            // 表示の更新や繰り返しの停止は無効な間も処理する
+            if self.disabled
+                && !matches!(
+                    e,
+                    CounterEvent::ValueChanged(_)
+                        | CounterEvent::StopRepeat
+                        | CounterEvent::CancelPress
+                        | CounterEvent::ScrubEnd
+                        | CounterEvent::DebounceElapsed
+                        | CounterEvent::ConfirmTimeout
+                        | CounterEvent::PulseEnd
+                        | CounterEvent::RateTick
+                        | CounterEvent::TypeAheadElapsed
+                        | CounterEvent::TextFocus(_)
+                        | CounterEvent::FocusLeft
+                        | CounterEvent::CloseMenu
+                        | CounterEvent::CloseNumpad
+                        | CounterEvent::CancelInlineEdit
+                        | CounterEvent::RevertEntry
+                )
+            {
+                return;
+            }
+
+            // リセットを伝えてもモデルが値を変えなかった場合，ValueChangedは届かない
+            // そのまま待っていると後の関係のない変化をリセットとして読み上げるため，別の操作が行われた時点で待つのをやめる
+            // タイマーからのイベントは操作ではないため，待ったままにする
+            if !matches!(
+                e,
+                CounterEvent::Reset
+                    | CounterEvent::ValueChanged(_)
+                    | CounterEvent::DebounceElapsed
+                    | CounterEvent::ConfirmTimeout
+                    | CounterEvent::PulseEnd
+                    | CounterEvent::RateTick
+                    | CounterEvent::FocusLeft
+            ) {
+                self.reset_announce_pending = false;
+            }
+
+            match e {
+                CounterEvent::Increment => {
+                    if self.accept_press() {
+                        self.step_once(cx, StepDirection::Increment);
+                    }
+                }
+                CounterEvent::Decrement => {
+                    if self.accept_press() {
+                        self.step_once(cx, StepDirection::Decrement);
+                    }
+                }
+                CounterEvent::StartRepeat(direction) => {
+                    // cooldownの間に押された場合は，繰り返しも含めて何もしない
+                    if !self.accept_press() {
+                        return;
+                    }
+                    // 押している間は変更後の値を仮の表示として見せるだけにし，ボタンの上で離したときに変化させる
+                    if self.preview_on_hold {
+                        if let Some(preview) = self.preview_value(*direction, self.current_step(cx))
+                        {
+                            self.previewing = Some(*direction);
+                            cx.toggle_class("preview", true);
+                            cx.emit(CounterTextEvent::SetDisplay(self.text_for(preview)));
+                        }
+                        return;
+                    }
+                    // 長押しでリセットする場合は，離すまで押していた時間が分からないため何もしない
+                    if self.long_press_reset.is_some() {
+                        self.pressed = Some((*direction, self.clock.now()));
+                        return;
+                    }
+                    self.step_once(cx, *direction);
+                    self.repeating = Some(*direction);
+                    self.current_interval = self.repeat_interval;
+                    // 最初の繰り返しまではrepeat_delayだけ待つ
+                    let delay = self.repeat_delay;
+                    self.repeat_deadline = Some(self.clock.now() + delay);
+                    cx.modify_timer(self.repeat_timer, |timer_state| {
+                        timer_state.set_interval(delay);
+                    });
+                    cx.start_timer(self.repeat_timer);
+                }
+                // タイマーは確かめる機会を作るだけで，繰り返すかどうかはclockの時刻で決める
+                // タイマーの通知が遅れた場合も，経過した時間の分だけまとめて繰り返す
+                CounterEvent::RepeatTick => {
+                    if let (Some(direction), Some(mut deadline)) =
+                        (self.repeating, self.repeat_deadline)
+                    {
+                        let now = self.clock.now();
+                        while deadline <= now {
+                            self.step_once(cx, direction);
+                            // 2回目以降はrepeat_intervalの間隔で繰り返す
+                            // 加速する場合は，繰り返すたびに間隔を縮めていく
+                            // 間隔が0でも止まるように，最低でも1ミリ秒は進める
+                            let interval = self.current_interval;
+                            if let Some((factor, min_interval)) = self.repeat_acceleration {
+                                self.current_interval =
+                                    interval.mul_f32(factor).max(min_interval.min(interval));
+                            }
+                            deadline += interval.max(Duration::from_millis(1));
+                        }
+                        self.repeat_deadline = Some(deadline);
+                        let wait = deadline - now;
+                        cx.modify_timer(self.repeat_timer, |timer_state| {
+                            timer_state.set_interval(wait);
+                        });
+                    }
+                }
+                CounterEvent::StopRepeat => {
+                    self.repeating = None;
+                    self.repeat_deadline = None;
+                    self.current_interval = self.repeat_interval;
+                    cx.stop_timer(self.repeat_timer);
+                    // 仮の表示をしていた場合は，ここで初めて変化させる
+                    // on_pressとon_mouse_upの両方から送られるが，clear_previewが方向を取り出すため1回だけ変化する
+                    // 押している間に無効になった場合は，表示を戻すだけで変化させない
+                    if let Some(direction) = self.clear_preview(cx) {
+                        if !self.disabled {
+                            self.step_once(cx, direction);
+                        }
+                    }
+                    // 押していた時間がthresholdを超えていればリセットし，短ければ通常通り変化させる
+                    // on_pressとon_mouse_upの両方から送られるため，takeで1回だけ処理する
+                    if let (Some((direction, started)), Some(threshold)) =
+                        (self.pressed.take(), self.long_press_reset)
+                    {
+                        if self.clock.now().duration_since(started) >= threshold {
+                            self.request_reset(cx);
+                        } else {
+                            self.step_once(cx, direction);
+                        }
+                    }
+                }
+                // 離す前にポインタがボタンから外れた場合は，仮の表示を取り消して変化させない
+                CounterEvent::CancelPress => {
+                    self.clear_preview(cx);
+                    self.repeating = None;
+                    self.repeat_deadline = None;
+                    self.current_interval = self.repeat_interval;
+                    self.pressed = None;
+                    cx.stop_timer(self.repeat_timer);
+                }
+                CounterEvent::Reset => self.request_reset(cx),
+                CounterEvent::ConfirmTimeout => self.disarm_reset(cx),
+                CounterEvent::SetToMin => {
+                    if let Some(min) = self.min {
+                        self.set_value(cx, min);
+                    }
+                }
+                CounterEvent::SetToMax => {
+                    if let Some(max) = self.max {
+                        self.set_value(cx, max);
+                    }
+                }
+                CounterEvent::CloseMenu => {
+                    cx.emit(CounterLayoutEvent::SetMenuOpen(false));
+                }
+                CounterEvent::PulseEnd => {
+                    cx.stop_timer(self.pulse_timer);
+                    cx.toggle_class("pulse", false);
+                }
+                CounterEvent::RateTick => {
+                    self.update_rate(cx);
+                }
+                // 入力の途中で無効になった場合は確定せずに取り消す
+                CounterEvent::TypeAheadElapsed => {
+                    if self.disabled {
+                        self.cancel_type_ahead(cx);
+                    } else {
+                        self.commit_type_ahead(cx);
+                    }
+                }
+                CounterEvent::TextFocus(focused) => {
+                    self.text_focused = *focused;
+                }
+                // FocusOutの間に内部の別の要素へのFocusInが届いていれば，blur_pendingは戻されている
+                CounterEvent::FocusLeft => {
+                    if self.blur_pending {
+                        self.blur_pending = false;
+                        self.focus_within = false;
+                        cx.toggle_class("focused", false);
+                        if let Some(callback) = &self.on_blur {
+                            (callback)(cx);
+                        }
+                    }
+                }
+                CounterEvent::ScrubStart(x) => {
+                    self.scrub_anchor = Some(*x);
+                    self.scrub_moved = false;
+                }
+                CounterEvent::ScrubMove(x) => {
+                    // 基準点からscrub_sensitivityピクセル動くごとに1step変化させる
+                    // 変化させた分だけ基準点を進めるため，ゆっくり動かしても端数が失われない
+                    if let Some(anchor) = self.scrub_anchor {
+                        let steps = ((*x - anchor) / self.scrub_sensitivity).trunc() as i32;
+                        if steps != 0 {
+                            self.scrub_moved = true;
+                            self.scrub_anchor =
+                                Some(anchor + steps as f32 * self.scrub_sensitivity);
+                            self.step_times(cx, steps);
+                        }
+                    }
+                }
+                CounterEvent::ScrubEnd => {
+                    // ドラッグせずに離した場合はタップとして入力パッドを開く
+                    // 無効な間もScrubEndは届くため，開くのは有効な場合だけにする
+                    let tapped = self.scrub_anchor.take().is_some() && !self.scrub_moved;
+                    if tapped && self.numpad && !self.disabled {
+                        cx.emit(CounterTextEvent::NumpadClear);
+                        cx.emit(CounterLayoutEvent::SetNumpadOpen(true));
+                    }
+                }
+                // 受け付けなかった場合は理由を表示し，入力パッドは開いたままにして直せるようにする
+                CounterEvent::SubmitNumpad(text) => {
+                    if self.submit_text(cx, text) {
+                        cx.emit(CounterLayoutEvent::SetNumpadOpen(false));
+                    }
+                }
+                CounterEvent::StartInlineEdit => {
+                    if self.inline_edit {
+                        cx.emit(CounterTextEvent::SetEntry(self.value.to_string()));
+                        cx.emit(CounterLayoutEvent::SetEditing(true));
+                    }
+                }
+                // 受け付けた場合だけLabelに戻す．受け付けなかった場合は理由を表示して編集を続ける
+                CounterEvent::SubmitInline(text) => {
+                    if self.submit_text(cx, text) {
+                        // テキスト入力はFocusOutを送らずに取り除かれることがあるため，ここで戻しておく
+                        self.text_focused = false;
+                        cx.emit(CounterLayoutEvent::SetEditing(false));
+                    }
+                }
+                CounterEvent::CancelInlineEdit => {
+                    self.revert_entry(cx);
+                    self.text_focused = false;
+                    cx.emit(CounterLayoutEvent::SetEditing(false));
+                }
+                CounterEvent::CloseNumpad => {
+                    cx.emit(CounterTextEvent::SetError(None));
+                    cx.emit(CounterLayoutEvent::SetNumpadOpen(false));
+                }
+                CounterEvent::SetValue(value) => self.set_value(cx, self.entered(*value)),
+                CounterEvent::SubmitEntry(text, enter) => {
+                    // commit_modeで受け付けない確定方法の場合や，数値として解析できない場合は入力を元に戻す
+                    let accepted = match self.commit_mode {
+                        CommitMode::OnEnter => *enter,
+                        CommitMode::OnBlur => !*enter,
+                        CommitMode::OnEnterOrBlur => true,
+                    };
+                    // 数値として解析できない場合は，入力を残したまま理由を表示して直せるようにする
+                    match self.parse_entry(text) {
+                        _ if !accepted => self.revert_entry(cx),
+                        Ok(value) => self.set_value(cx, self.entered(value)),
+                        Err(message) => self.reject_entry(cx, message),
+                    }
+                }
+                CounterEvent::RevertEntry => self.revert_entry(cx),
+                // 最後の変化からdelayが経っていなければ，残りの時間だけ待ち直す
+                CounterEvent::DebounceElapsed => {
+                    let Some(deadline) = self.debounce_deadline else {
+                        return;
+                    };
+                    let now = self.clock.now();
+                    if now < deadline {
+                        let wait = deadline - now;
+                        cx.modify_timer(self.debounce_timer, |timer_state| {
+                            timer_state.set_interval(wait);
+                        });
+                        return;
+                    }
+                    self.debounce_deadline = None;
+                    cx.stop_timer(self.debounce_timer);
+                    if let Some((_, callback)) = &self.on_change_debounced {
+                        (callback)(cx, self.value);
+                    }
+                }
+                CounterEvent::ValueChanged(value) => {
+                    // ValueChangedはモデルの更新がLensに反映された後に届くため，
+                    // on_changeには更新後の値を渡すことができる
+                    // 構築時の通知では値が変わらないため呼び出さない
+                    // precisionが設定されている場合は表示する桁数に丸めて保持する
+                    // モデルで0.1を10回足した結果が0.9999999999999999になっていても，1.0として表示や範囲の判定を行う
+                    let value = self.quantized(*value);
+                    let changed = self.value != value;
+                    let was_at_min = self.at_min();
+                    let was_at_max = self.at_max();
+                    let previous = self.value;
+                    self.value = value;
+                    // リセットによる変化であれば読み上げ，それ以外の変化では前回の読み上げを消しておく
+                    // 消しておくことで，同じ値に続けてリセットした場合も文字列が変わり，再び読み上げられる
+                    if changed {
+                        if std::mem::take(&mut self.reset_announce_pending) {
+                            self.announce_reset(cx);
+                        } else {
+                            cx.emit(CounterTextEvent::SetAnnouncement(String::new()));
+                        }
+                    }
+                    if changed {
+                        if let Some(rate) = &mut self.rate {
+                            rate.record(self.clock.now(), self.value - previous);
+                            cx.start_timer(self.rate_timer);
+                        }
+                    }
+                    self.update_rate(cx);
+                    // 表示とテキスト入力の内容も新しい値に合わせる
+                    cx.emit(CounterTextEvent::SetDisplay(self.display_text()));
+                    cx.emit(CounterTextEvent::SetEntry(self.value.to_string()));
+                    if changed {
+                        if let Some(callback) = &self.on_change {
+                            (callback)(cx, self.value);
+                        }
+                        let value = self.value;
+                        self.subscribers.retain(|sender| sender.send(value));
+                        // 変化するたびにタイマーを止めて待ち時間を最初から計り直す
+                        if let Some((delay, _)) = &self.on_change_debounced {
+                            let delay = *delay;
+                            self.debounce_deadline = Some(self.clock.now() + delay);
+                            cx.stop_timer(self.debounce_timer);
+                            cx.modify_timer(self.debounce_timer, |timer_state| {
+                                timer_state.set_interval(delay);
+                            });
+                            cx.start_timer(self.debounce_timer);
+                        }
+                    }
+                    // 上限・下限に達した瞬間だけ呼び出し，達した状態でさらに押された場合は呼び出さない
+                    if !was_at_min && self.at_min() {
+                        if let Some(callback) = &self.on_min_reached {
+                            (callback)(cx);
+                        }
+                    }
+                    if !was_at_max && self.at_max() {
+                        if let Some(callback) = &self.on_max_reached {
+                            (callback)(cx);
+                        }
+                    }
+                    // 上限・下限に達したときにクラスを付与し，CSSでボタンをグレーアウトできるようにする
+                    // 反対側の端へ戻る場合は，上限・下限でもボタンを押せることを表すために灰色にしない
+                    cx.toggle_class("at-min", !self.wrap && self.at_min());
+                    cx.toggle_class("at-max", !self.wrap && self.at_max());
+                    cx.toggle_class("placeholder", self.is_placeholder());
+                    // 負の値を赤く表示できるようにクラスを付与する
+                    cx.toggle_class("negative", self.value < T::one() - T::one());
+                    cx.toggle_class(
+                        "reached",
+                        self.target.is_some_and(|target| self.value >= target),
+                    );
+                    // 値の範囲ごとのクラスは，該当する1つだけを付与する
+                    let active = self.threshold_class().map(str::to_owned);
+                    for (_, class) in &self.thresholds {
+                        cx.toggle_class(class, active.as_deref() == Some(class.as_str()));
+                    }
+                    // 変化するたびにpulseクラスを付け直し，短い時間の後に外す
+                    // 外す前に再び変化した場合はタイマーを計り直すため，連続した変化でも表示が止まらない
+                    if changed && self.animate_on_change {
+                        cx.toggle_class("pulse", true);
+                        cx.stop_timer(self.pulse_timer);
+                        cx.start_timer(self.pulse_timer);
+                    }
+                }
+            }
+        });
+
+        // キーボードのイベントはフォーカスされているビューから親に向かって伝搬するため，
+        // Counter内のボタンにフォーカスがある場合も受け取ることができる
+        // ホイールのイベントも同様に，ポインタの下にある子要素からCounterまで伝搬する
+        event.map(|window_event, meta| match window_event {
+            WindowEvent::KeyDown(code, _) => {
+                if !self.keyboard || self.disabled {
+                    return;
+                }
+                // キーによる操作もCounterEventと同じように，リセットの読み上げを待つのをやめる
+                // Ctrl+0によるリセットは，この後に送るCounterEvent::Resetで改めて待つ
+                self.reset_announce_pending = false;
+                // 修飾キーも含めて一致した場合だけリセットする．Ctrlを押していない0では反応しない
+                if self.reset_shortcut.code == *code
+                    && self.reset_shortcut.modifiers == *cx.modifiers()
+                {
+                    cx.emit(CounterEvent::<T>::Reset);
+                    meta.consume();
+                    return;
+                }
+                // 数字キーを続けて押すと値を入力できる．修飾キーを押している場合やテキスト入力では何もしない
+                // Enterですぐに確定し，Escapeで取り消す．押さなくなってから一定時間が経つと確定する
+                if !self.text_focused {
+                    if let Some(digit) = digit_char(code).filter(|_| cx.modifiers().is_empty()) {
+                        self.push_type_ahead(cx, digit);
+                        meta.consume();
+                        return;
+                    }
+                    if !self.type_ahead.is_empty() {
+                        match code {
+                            Code::Enter | Code::NumpadEnter => {
+                                self.commit_type_ahead(cx);
+                                meta.consume();
+                                return;
+                            }
+                            Code::Escape => {
+                                self.cancel_type_ahead(cx);
+                                meta.consume();
+                                return;
+                            }
+                            _ => {}
+                        }
+                    }
+                }
+                match code {
+                    Code::ArrowUp | Code::ArrowRight => {
+                        self.step_once(cx, StepDirection::Increment);
+                        meta.consume();
+                    }
+                    Code::ArrowDown | Code::ArrowLeft => {
+                        self.step_once(cx, StepDirection::Decrement);
+                        meta.consume();
+                    }
+                    // Page Up・Page Downでは大きく変化させる
+                    Code::PageUp => {
+                        self.page(cx, StepDirection::Increment);
+                        meta.consume();
+                    }
+                    Code::PageDown => {
+                        self.page(cx, StepDirection::Decrement);
+                        meta.consume();
+                    }
+                    // Home・Endでは下限・上限まで移動する．rangeが設定されていない場合は何もしない
+                    Code::Home if self.min.is_some() => {
+                        cx.emit(CounterEvent::<T>::SetToMin);
+                        meta.consume();
+                    }
+                    Code::End if self.max.is_some() => {
+                        cx.emit(CounterEvent::<T>::SetToMax);
+                        meta.consume();
+                    }
+                    _ => {}
+                }
+            }
+            // Tabキーによる移動でもクリックでも，フォーカスが変わるとFocusIn・FocusOutが送られる
+            // 内部のボタンやテキスト入力のどれかにフォーカスがある間はfocusedクラスを付与し，
+            // CSSからCounter全体を囲むフォーカスリングを描けるようにする
+            // 内部の要素の間でフォーカスが移る場合は，FocusOutの直後にFocusInが届く
+            // そのためFocusOutではすぐに外さず，後から送るFocusLeftまでにFocusInが届かなかった場合だけ外す
+            // on_focus・on_blurも，フォーカスがCounterの外から入ったとき・外へ出たときだけ呼び出す
+            WindowEvent::FocusIn => {
+                self.blur_pending = false;
+                if !self.focus_within {
+                    self.focus_within = true;
+                    cx.toggle_class("focused", true);
+                    if let Some(callback) = &self.on_focus {
+                        (callback)(cx);
+                    }
+                }
+            }
+            WindowEvent::FocusOut => {
+                if self.focus_within {
+                    self.blur_pending = true;
+                    cx.emit(CounterEvent::<T>::FocusLeft);
+                }
+            }
+            // 右クリックでコンテキストメニューを開く
+            WindowEvent::MouseDown(MouseButton::Right) => {
+                if !self.context_menu || self.disabled {
+                    return;
+                }
+                cx.emit(CounterLayoutEvent::SetMenuOpen(true));
+                meta.consume();
+            }
+            WindowEvent::MouseScroll(_, y) => {
+                if !self.wheel || self.disabled {
+                    return;
+                }
+                self.reset_announce_pending = false;
+                // トラックパッドでは1回のイベントの移動量が小さいため，1行分に達するまで貯めてから変化させる
+                // 上に回すと増加し，下に回すと減少する
+                self.wheel_delta += *y;
+                let steps = self.wheel_delta.trunc() as i32;
+                if steps != 0 {
+                    self.wheel_delta -= steps as f32;
+                    self.step_times(cx, steps);
+                }
+                meta.consume();
+            }
+            _ => {}
+        });
+    }
+}
+
+impl<T: CounterValue> Counter<T> {
+    // スマートフォンの数量選択のような，角の丸い小さなCounterを作る
+    // 減少ボタン，値，増加ボタンの順に並べ，見た目はpillクラスでCSSから指定する
+    // イベントやモディファイアは通常のCounterと同じものを使用できる
+    pub fn pill<L>(cx: &mut Context, lens: L) -> Handle<Self>
+    where
+        L: Lens<Target = T>,
+    {
+        Self::dense(cx, lens, "pill")
+    }
+
+    // 表の中など狭い場所に置くための，余白をほとんど持たない幅の決まったCounterを作る
+    // pillと同じく減少ボタン，値，増加ボタンの順に隙間なく並べ，見た目はcompactクラスでCSSから指定する
+    pub fn compact<L>(cx: &mut Context, lens: L) -> Handle<Self>
+    where
+        L: Lens<Target = T>,
+    {
+        Self::dense(cx, lens, "compact")
+    }
+
+    // pillとcompactに共通する，減少ボタン・値・増加ボタンだけを並べたCounterを作る
+    // 見た目の違いはclassで付与するクラスとCSSで表す
+    fn dense<L>(cx: &mut Context, lens: L, class: &'static str) -> Handle<Self>
+    where
+        L: Lens<Target = T>,
+    {
+        let mut handle = Self::new(cx, lens).button_labels("+", "−").class(class);
+        let entity = handle.entity();
+        handle
+            .context()
+            .emit_to(entity, CounterLayoutEvent::SetCompact(true));
+        handle
+    }
+
+    // CounterConfigの設定を適用したCounterを作る
+    // コールバックは設定に含められないため，返されたHandleにモディファイアで追加する
+    pub fn from_config<L>(cx: &mut Context, lens: L, config: &CounterConfig<T>) -> Handle<Self>
+    where
+        L: Lens<Target = T>,
+    {
+        let mut handle = Self::new(cx, lens).orientation(config.orientation);
+        if let Some(step) = config.step {
+            handle = handle.step(step);
+        }
+        handle = handle.modify(|counter| {
+            counter.min = config.min;
+            counter.max = config.max;
+        });
+        if let Some(class) = &config.class {
+            handle = handle.class(class);
+        }
+        handle
+    }
+
+    // ボタンを持たず，値を表示するだけのCounterを作る
+    // Labelの見た目やformat，precisionなどのモディファイアは通常のCounterと同じものを使用できる
+    pub fn display<L>(cx: &mut Context, lens: L) -> Handle<Self>
+    where
+        L: Lens<Target = T>,
+    {
+        let mut handle = Self::new(cx, lens).keyboard(false).class("display");
+        let entity = handle.entity();
+        handle
+            .context()
+            .emit_to(entity, CounterLayoutEvent::SetDisplayOnly(true));
+        handle
+    }
+
+    // Viewを使用するにはコンストラクタでViewをContextに追加する必要がある
+    // データバインディングを追加するにはコンストラクタでLensを引数に渡す必要がある．またLensはジェネリックを使用してLens traitを実装する任意の型を受け取っている
+    // 番号は自動的に割り当てられる
+    pub fn new<L>(cx: &mut Context, lens: L) -> Handle<Self>
+    where
+        L: Lens<Target = T>,
+    {
+        Self::new_with_id(cx, lens, CounterId::next())
+    }
+
+    // 指定した番号を持つCounterを作る．番号が重ならないようにするのは呼び出し側の責任とする
+    pub fn new_with_id<L>(cx: &mut Context, lens: L, id: CounterId) -> Handle<Self>
+    where
+        L: Lens<Target = T>,
+    {
+        // タイマーはCounter自身にRepeatTickを送るだけで，繰り返す時刻になったかの判定はeventメソッドで行う
+        let repeat_timer = cx.add_timer(Duration::from_millis(80), None, |cx, action| {
+            if let TimerAction::Tick(_) = action {
+                cx.emit(CounterEvent::<T>::RepeatTick);
+            }
+        });
+
+        // 一度だけ通知すればよいため，待ち時間が経過していれば最初のTickで止める
+        // 止めた時点のキャッシュが最後の値になるため，連続した変化の後でも最終的な値が必ず届く
+        let debounce_timer = cx.add_timer(Duration::from_millis(300), None, |cx, action| {
+            if let TimerAction::Tick(_) = action {
+                cx.emit(CounterEvent::<T>::DebounceElapsed);
+            }
+        });
+
+        // 確認待ちの時間が経過したら1回だけ通知する
+        let confirm_timer = cx.add_timer(Duration::from_secs(3), None, |cx, action| {
+            if let TimerAction::Tick(_) = action {
+                cx.emit(CounterEvent::<T>::ConfirmTimeout);
+            }
+        });
+
+        // 強調表示の時間が経過したら1回だけ通知する
+        let pulse_timer = cx.add_timer(Duration::from_millis(150), None, |cx, action| {
+            if let TimerAction::Tick(_) = action {
+                cx.emit(CounterEvent::<T>::PulseEnd);
+            }
+        });
+
+        // 変化が記録されている間は一定の間隔で表示を更新し，記録がなくなったら止める
+        let rate_timer = cx.add_timer(Duration::from_millis(500), None, |cx, action| {
+            if let TimerAction::Tick(_) = action {
+                cx.emit(CounterEvent::<T>::RateTick);
+            }
+        });
+
+        // 数字キーが押されなくなってから一定時間後に1回だけ通知し，入力中の値を確定する
+        let type_ahead_timer = cx.add_timer(TYPE_AHEAD_DELAY, None, |cx, action| {
+            if let TimerAction::Tick(_) = action {
+                cx.emit(CounterEvent::<T>::TypeAheadElapsed);
+            }
+        });
+
+        let value = lens.get(cx);
+
+        // Viewトレイトによって提供される build()関数は、カスタムViewのコンテンツを構築するために使用できるクロージャを引数に取る。
+        Self {
+            // エラーになるため,初期化時にコールバックをNoneに設定する
+            on_increment: None,
+            on_decrement: None,
+            on_reset: None,
+            default_value: None,
+            on_change: None,
+            on_change_debounced: None,
+            debounce_timer,
+            debounce_deadline: None,
+            confirm_reset: None,
+            reset_armed: false,
+            confirm_timer,
+            animate_on_change: false,
+            pulse_timer,
+            on_set: None,
+            on_min_reached: None,
+            on_max_reached: None,
+            id,
+            on_step: None,
+            before_step: None,
+            on_focus: None,
+            on_blur: None,
+            on_error: None,
+            on_overflow: None,
+            on_feedback: None,
+            format: None,
+            precision: None,
+            locale: Locale::Plain,
+            unit: None,
+            negative_style: NegativeStyle::Minus,
+            abbreviate: false,
+            placeholder: None,
+            placeholder_base: None,
+            // stepが設定されない場合は従来通り1ずつ変化させる
+            step: T::one(),
+            step_bound: false,
+            shift_multiplier: 1,
+            page_step: None,
+            min: None,
+            max: None,
+            value,
+            repeat_timer,
+            repeat_deadline: None,
+            repeat_delay: Duration::from_millis(400),
+            repeat_interval: Duration::from_millis(80),
+            repeat_acceleration: None,
+            current_interval: Duration::from_millis(80),
+            repeating: None,
+            scrub_sensitivity: 10.0,
+            scrub_anchor: None,
+            scrub_moved: false,
+            long_press_reset: None,
+            pressed: None,
+            keyboard: true,
+            reset_shortcut: KeyChord::new(Modifiers::CTRL, Code::Digit0),
+            // スクロールできる領域の中で意図せず値が変わらないように，既定では無効にする
+            wheel: false,
+            context_menu: false,
+            numpad: false,
+            inline_edit: false,
+            allow_expressions: false,
+            preview_on_hold: false,
+            previewing: None,
+            reset_announcement: String::from("Reset to {value}"),
+            reset_announce_pending: false,
+            rate: None,
+            rate_timer,
+            type_ahead: String::new(),
+            type_ahead_timer,
+            text_focused: false,
+            focus_within: false,
+            blur_pending: false,
+            subscribers: Vec::new(),
+            thresholds: Vec::new(),
+            target: None,
+            increment_content: None,
+            decrement_content: None,
+            leading_icon: None,
+            cooldown: None,
+            last_press: None,
+            clock: Rc::new(SystemClock),
+            wheel_delta: 0.0,
+            disabled: false,
+            increment_enabled: true,
+            decrement_enabled: true,
+            snap: false,
+            wrap: false,
+            commit_mode: CommitMode::OnEnterOrBlur,
+        }
+        .build(cx, |cx| {
+            // 表示用のモデルをCounterに関連付ける
+            // formatはbuildの後に設定されるため，整形された文字列は最初のValueChangedで反映される
+            CounterText {
+                entry: value.to_string(),
+                display: value.to_string(),
+                increment_label: String::from("Increment"),
+                decrement_label: String::from("Decrement"),
+                reset_label: String::from("Reset"),
+                increment_tooltip: String::new(),
+                decrement_tooltip: String::new(),
+                increment_custom: false,
+                decrement_custom: false,
+                error: None,
+                numpad_entry: String::new(),
+                rate: String::new(),
+                announcement: String::new(),
+            }
+            .build(cx);
+
+            // 並べ方の設定をCounterに関連付ける
+            CounterLayoutState {
+                orientation: CounterOrientation::Horizontal,
+                direction: LayoutDirection::LeftToRight,
+                compact: false,
+                layout: CounterLayout::MinusValuePlus,
+                menu_open: false,
+                numpad_open: false,
+                editing: false,
+                show_rate: false,
+                leading_icon: false,
+                accent: None,
+                display_only: false,
+                increment_enabled: true,
+                decrement_enabled: true,
+                count_class: String::from("count"),
+                value_width: Units::Auto,
+                autofocus: None,
+            }
+            .build(cx);
+
+            // アプリケーションにビューを追加する
+            // ビューの構成はHStackのようなコンテナビューを使って行う
+            // HStackは水平方向に，VStackは垂直方向にビューを並べる
+            // デフォルトではスタックは親ビュー(window)を埋めるように拡張される
+            // レイアウトシステムについてはmorphormのドキュメントを参照
+            // 表示だけのCounterでは値のLabelだけを作る
+            Binding::new(cx, CounterLayoutState::display_only, |cx, display_only| {
+                if display_only.get(cx) {
+                    Self::value_label(cx);
+                } else {
+                    Self::controls(cx);
+                }
+            });
+
+            // 直近の変化量はボタンや値の下に小さく表示する
+            Binding::new(cx, CounterLayoutState::show_rate, |cx, show_rate| {
+                if show_rate.get(cx) {
+                    Label::new(cx, CounterText::rate).class("rate");
+                }
+            });
+
+            // リセットしたときだけ読み上げるライブリージョン．画面には表示しない
+            // 増加・減少による変化は値のLabelが読み上げるため，ここでは扱わない
+            Label::new(cx, CounterText::announcement)
+                .live(Live::Polite)
+                .class("announcement");
+
+            Self::context_menu(cx);
+            Self::numpad_popup(cx);
+        })
+        // Lensの値が変化するたびにCounterへ通知し，保持している値を更新する
+        .bind(lens, |mut handle, lens| {
+            let value = lens.get(&handle);
+            let entity = handle.entity();
+            handle
+                .context()
+                .emit_to(entity, CounterEvent::ValueChanged(value));
+        })
+        // キーボードのイベントを受け取れるようにTabキーでフォーカスできるようにする
+        .navigable(true)
+        // スクリーンリーダーからはボタンと値をまとめたグループとして扱う
+        .role(Role::Group)
+        .name("Counter")
+    }
+
+    // ボタンを追加する
+    // EventContextを使用してイベントを発行する
+    // ツリーを辿ってCounterに伝搬される
+    // 押された瞬間に1回変化させ，押し続けている間は繰り返す
+    // 離したときやポインタが外れたときは繰り返しを止める
+    // ツールチップはbuildの後にtooltipsで設定されるため，Bindingで変更されたときにボタンを作り直す
+    fn decrement_button(cx: &mut Context) {
+        Binding::new(cx, CounterLayoutState::accent, |cx, accent| {
+            let accent = accent.get(cx);
+            Binding::new(cx, CounterText::decrement_tooltip, move |cx, tooltip| {
+                let tooltip = tooltip.get(cx);
+                let button = Button::new(cx, |cx| {
+                    Self::button_content(
+                        cx,
+                        CounterText::decrement_custom,
+                        StepDirection::Decrement,
+                    )
+                })
+                .on_press_down(|ex| {
+                    ex.emit(CounterEvent::<T>::StartRepeat(StepDirection::Decrement))
+                })
+                .on_press(|ex| ex.emit(CounterEvent::<T>::StopRepeat))
+                .on_mouse_up(|ex, _| ex.emit(CounterEvent::<T>::StopRepeat))
+                .on_hover_out(|ex| ex.emit(CounterEvent::<T>::CancelPress))
+                // ボタンの表示が記号の場合でもスクリーンリーダーが読み上げられるように名前を設定する
+                .role(Role::Button)
+                .name("Decrement")
+                .disabled(CounterLayoutState::decrement_enabled.map(|enabled| !enabled))
+                .class("dec");
+                let button = Self::with_accent(button, accent);
+                let button = Self::focus_when(button, FocusTarget::DecrementButton);
+                Self::with_tooltip(button, tooltip);
+            });
+        });
+    }
+
+    fn increment_button(cx: &mut Context) {
+        Binding::new(cx, CounterLayoutState::accent, |cx, accent| {
+            let accent = accent.get(cx);
+            Binding::new(cx, CounterText::increment_tooltip, move |cx, tooltip| {
+                let tooltip = tooltip.get(cx);
+                let button = Button::new(cx, |cx| {
+                    Self::button_content(
+                        cx,
+                        CounterText::increment_custom,
+                        StepDirection::Increment,
+                    )
+                })
+                .on_press_down(|ex| {
+                    ex.emit(CounterEvent::<T>::StartRepeat(StepDirection::Increment))
+                })
+                .on_press(|ex| ex.emit(CounterEvent::<T>::StopRepeat))
+                .on_mouse_up(|ex, _| ex.emit(CounterEvent::<T>::StopRepeat))
+                .on_hover_out(|ex| ex.emit(CounterEvent::<T>::CancelPress))
+                .role(Role::Button)
+                .name("Increment")
+                .disabled(CounterLayoutState::increment_enabled.map(|enabled| !enabled))
+                .class("inc");
+                let button = Self::with_accent(button, accent);
+                let button = Self::focus_when(button, FocusTarget::IncrementButton);
+                Self::with_tooltip(button, tooltip);
+            });
+        });
+    }
+
+    // 増加・減少ボタンの中身を作る
+    // 関数が設定されていればそれを呼び出し，なければbutton_labelsの文字列を表示する
+    // 関数はbuildの後に設定されるため，設定されたことをcustomで受け取って作り直す
+    fn button_content(
+        cx: &mut Context,
+        custom: impl Lens<Target = bool>,
+        direction: StepDirection,
+    ) {
+        Binding::new(cx, custom, move |cx, custom| {
+            let content = cx.data::<Self>().and_then(|counter| match direction {
+                StepDirection::Increment => counter.increment_content.clone(),
+                StepDirection::Decrement => counter.decrement_content.clone(),
+            });
+            match content.filter(|_| custom.get(cx)) {
+                Some(content) => (content)(cx),
+                None => match direction {
+                    StepDirection::Increment => {
+                        Label::new(cx, CounterText::increment_label);
+                    }
+                    StepDirection::Decrement => {
+                        Label::new(cx, CounterText::decrement_label);
+                    }
+                },
+            }
+        });
+    }
+
+    // accent_colorが設定されていれば，ボタンの背景色をインラインのスタイルで上書きする
+    // 設定されていない場合はスタイルシートの色のままにする
+    fn with_accent<V: View>(handle: Handle<V>, accent: Option<Color>) -> Handle<V> {
+        match accent {
+            Some(color) => handle.background_color(color),
+            None => handle,
+        }
+    }
+
+    // autofocusでtargetが指定されたときに，この子要素にフォーカスする
+    // フォーカスするのは1回だけとし，その後はautofocusをNoneに戻す
+    // 戻さない場合，値の変化などで子要素が作り直されるたびにフォーカスを奪ってしまう
+    fn focus_when<V: View>(handle: Handle<V>, target: FocusTarget) -> Handle<V> {
+        handle.bind(
+            CounterLayoutState::autofocus,
+            move |mut handle, autofocus| {
+                if autofocus.get(&handle) == Some(target) {
+                    let entity = handle.entity();
+                    handle.context().with_current(entity, |cx| {
+                        cx.focus();
+                        cx.emit(CounterLayoutEvent::ClearAutofocus);
+                    });
+                }
+            },
+        )
+    }
+
+    // 文字列が空でなければ，ポインタを乗せてしばらくすると表示されるツールチップを設定する
+    // 表示までの待ち時間やポインタが離れたときに隠す処理はviziaのTooltipが行う
+    fn with_tooltip(button: Handle<Button>, text: String) {
+        if text.is_empty() {
+            return;
+        }
+        button.tooltip(move |cx| {
+            let text = text.clone();
+            Tooltip::new(cx, move |cx| {
+                Label::new(cx, text);
+            })
+        });
+    }
+
+    // ボタンやテキスト入力を含む操作できるCounterの子要素を作る
+    // orientationはbuildの後に設定されるため，Bindingで変更されたときに作り直す
+    fn controls(cx: &mut Context) {
+        Binding::new(cx, CounterLayoutState::orientation, |cx, orientation| {
+            match orientation.get(cx) {
+                CounterOrientation::Horizontal => {
+                    Binding::new(cx, CounterLayoutState::compact, |cx, compact| {
+                        let compact = compact.get(cx);
+                        Binding::new(cx, CounterLayoutState::direction, move |cx, direction| {
+                            let direction = direction.get(cx);
+                            Binding::new(cx, CounterLayoutState::layout, move |cx, layout| {
+                                let layout = layout.get(cx);
+                                HStack::new(cx, |cx| {
+                                    for part in horizontal_parts(direction, compact, layout) {
+                                        Self::part(cx, part);
+                                    }
+                                })
+                                .class("row")
+                                .toggle_class("rtl", direction == LayoutDirection::RightToLeft);
+                            });
+                        });
+                    });
+                }
+                // 縦に並べる場合は増加ボタンを上に配置する．順序は固定のため，layoutの設定は使わない
+                CounterOrientation::Vertical => {
+                    VStack::new(cx, |cx| {
+                        Self::increment_button(cx);
+                        Self::value_label(cx);
+                        Self::decrement_button(cx);
+                        Self::entry(cx);
+                        Self::reset_button(cx);
+                    })
+                    .class("column");
+                }
+            }
+        });
+    }
+
+    // 右クリックで開くメニュー．開いている間だけPopupを作る
+    // 項目を選ぶかメニューの外をクリックすると閉じる
+    fn context_menu(cx: &mut Context) {
+        Binding::new(cx, CounterLayoutState::menu_open, |cx, open| {
+            if !open.get(cx) {
+                return;
+            }
+            Popup::new(cx, |cx| {
+                Self::menu_item(cx, "Reset", || CounterEvent::Reset);
+                Self::menu_item(cx, "Set to max", || CounterEvent::SetToMax);
+                Self::menu_item(cx, "Set to min", || CounterEvent::SetToMin);
+            })
+            .on_blur(|ex| ex.emit(CounterEvent::<T>::CloseMenu))
+            .role(Role::Menu)
+            .class("context-menu");
+        });
+    }
+
+    // 値を直接入力するための数字の入力パッド
+    // 入力中の文字列と受け付けなかった理由を上に表示し，その下に数字のボタンを電話と同じ並びで置く
+    fn numpad_popup(cx: &mut Context) {
+        Binding::new(cx, CounterLayoutState::numpad_open, |cx, open| {
+            if !open.get(cx) {
+                return;
+            }
+            Popup::new(cx, |cx| {
+                Label::new(cx, CounterText::numpad_entry).class("numpad-entry");
+                Label::new(
+                    cx,
+                    CounterText::error.map(|error| error.clone().unwrap_or_default()),
+                )
+                .display(CounterText::error.map(Option::is_some))
+                .class("error");
+                for row in ["123", "456", "789"] {
+                    HStack::new(cx, |cx| {
+                        for digit in row.chars() {
+                            Self::numpad_key(cx, digit);
+                        }
+                    })
+                    .class("numpad-row");
+                }
+                HStack::new(cx, |cx| {
+                    Button::new(cx, |cx| Label::new(cx, "\u{232B}"))
+                        .on_press(|ex| ex.emit(CounterTextEvent::NumpadBackspace))
+                        .name("Backspace")
+                        .class("numpad-key");
+                    Self::numpad_key(cx, '0');
+                    Button::new(cx, |cx| Label::new(cx, "OK"))
+                        .on_press(|ex| {
+                            let text = CounterText::numpad_entry.get(ex);
+                            ex.emit(CounterEvent::<T>::SubmitNumpad(text));
+                        })
+                        .class("numpad-key");
+                })
+                .class("numpad-row");
+                Button::new(cx, |cx| Label::new(cx, "Cancel"))
+                    .on_press(|ex| ex.emit(CounterEvent::<T>::CloseNumpad))
+                    .class("numpad-cancel");
+            })
+            .on_blur(|ex| ex.emit(CounterEvent::<T>::CloseNumpad))
+            .role(Role::Dialog)
+            .class("numpad");
+        });
+    }
+
+    fn numpad_key(cx: &mut Context, digit: char) {
+        Button::new(cx, move |cx| Label::new(cx, &digit.to_string()))
+            .on_press(move |ex| ex.emit(CounterTextEvent::NumpadInput(digit)))
+            .class("numpad-key");
+    }
+
+    // メニューの項目．選ばれたときにeventをCounterに送ってからメニューを閉じる
+    fn menu_item(cx: &mut Context, text: &'static str, event: fn() -> CounterEvent<T>) {
+        Button::new(cx, move |cx| Label::new(cx, text))
+            .on_press(move |ex| {
+                ex.emit(event());
+                ex.emit(CounterEvent::<T>::CloseMenu);
+            })
+            .role(Role::MenuItem)
+            .name(text)
+            .class("menu-item");
+    }
+
+    // 子要素の種類に応じたビューを作る
+    fn part(cx: &mut Context, part: CounterPart) {
+        match part {
+            CounterPart::Decrement => Self::decrement_button(cx),
+            CounterPart::Increment => Self::increment_button(cx),
+            CounterPart::Value => Self::value_label(cx),
+            CounterPart::Entry => Self::entry(cx),
+            CounterPart::Reset => Self::reset_button(cx),
+        }
+    }
+
+    // countが更新されるたび，ビューを更新するバインディングが設定される
+    // 表示する文字列はformatで整形された値をCounterTextから受け取る
+    // クラス名はcount_classで変更できるため，Bindingで変更されたときに作り直す
+    // leading_iconが設定されている場合は，アイコンと値を1つの行にまとめて並べる
+    fn value_label(cx: &mut Context) {
+        Binding::new(cx, CounterLayoutState::leading_icon, |cx, leading_icon| {
+            if leading_icon.get(cx) {
+                HStack::new(cx, |cx| {
+                    Self::icon(cx);
+                    Self::value_or_entry(cx);
+                })
+                .class("value-group");
+            } else {
+                Self::value_or_entry(cx);
+            }
+        });
+    }
+
+    // 呼び出し側が作ったアイコン
+    // ドラッグやタップは値のLabelで受け取るため，アイコンはポインタのイベントを受け取らないようにする
+    fn icon(cx: &mut Context) {
+        let builder = cx
+            .data::<Self>()
+            .and_then(|counter| counter.leading_icon.clone());
+        if let Some(builder) = builder {
+            HStack::new(cx, |cx| (builder)(cx))
+                .hoverable(false)
+                .class("leading-icon");
+        }
+    }
+
+    fn value_or_entry(cx: &mut Context) {
+        // ダブルクリックで編集している間はLabelの代わりにテキスト入力を表示する
+        Binding::new(cx, CounterLayoutState::editing, |cx, editing| {
+            if editing.get(cx) {
+                Self::inline_entry(cx);
+            } else {
+                Self::display_label(cx);
+            }
+        });
+    }
+
+    fn display_label(cx: &mut Context) {
+        Binding::new(cx, CounterLayoutState::count_class, |cx, class| {
+            let class = class.get(cx);
+            // 値が変化したときにスクリーンリーダーが読み上げるようにライブリージョンとする
+            // 押したまま横にドラッグすると値を変化させることができる
+            // ポインタがLabelの外に出ても移動を受け取れるように，押している間はマウスをキャプチャする
+            // 移動せずにクリックした場合は値を変化させない
+            Label::new(cx, CounterText::display)
+                .live(Live::Polite)
+                .hoverable(true)
+                .on_mouse_down(|ex, button| {
+                    if button == MouseButton::Left {
+                        ex.capture();
+                        let x = ex.mouse().cursorx;
+                        ex.emit(CounterEvent::<T>::ScrubStart(x));
+                    }
+                })
+                .on_mouse_move(|ex, x, _| ex.emit(CounterEvent::<T>::ScrubMove(x)))
+                .on_mouse_up(|ex, button| {
+                    if button == MouseButton::Left {
+                        ex.release();
+                        ex.emit(CounterEvent::<T>::ScrubEnd);
+                    }
+                })
+                .on_double_click(|ex, button| {
+                    if button == MouseButton::Left {
+                        ex.emit(CounterEvent::<T>::StartInlineEdit);
+                    }
+                })
+                .min_width(CounterLayoutState::value_width)
+                .class(&class)
+                .class("value");
+        });
+    }
+
+    // 値のLabelの位置でその場で編集するためのテキスト入力
+    // 作られたときにフォーカスして編集を始め，すぐに入力できるようにする
+    fn inline_entry(cx: &mut Context) {
+        let mut entry = Textbox::new(cx, CounterText::entry)
+            .on_edit(|ex, text| ex.emit(CounterTextEvent::SetEntry(text)))
+            .on_submit(|ex, text, _| ex.emit(CounterEvent::<T>::SubmitInline(text)))
+            .on_cancel(|ex| ex.emit(CounterEvent::<T>::CancelInlineEdit))
+            .on_focus_in(|ex| ex.emit(CounterEvent::<T>::TextFocus(true)))
+            .on_focus_out(|ex| ex.emit(CounterEvent::<T>::TextFocus(false)))
+            .toggle_class("invalid", CounterText::error.map(Option::is_some))
+            .min_width(CounterLayoutState::value_width)
+            .class("value")
+            .class("inline-entry");
+        let entity = entry.entity();
+        entry.context().with_current(entity, |cx| {
+            cx.focus();
+            cx.emit(TextEvent::StartEdit);
+        });
+    }
+
+    // 値を直接入力するためのテキストボックス
+    // Enterで確定し，数値として解釈できない場合は現在の値に戻す
+    fn entry(cx: &mut Context) {
+        let entry = Textbox::new(cx, CounterText::entry)
+            .on_edit(|ex, text| ex.emit(CounterTextEvent::SetEntry(text)))
+            // Enterで確定したか，フォーカスが外れて確定したかをCounterに伝え，commit_modeに応じて処理する
+            .on_submit(|ex, text, enter| ex.emit(CounterEvent::<T>::SubmitEntry(text, enter)))
+            .on_focus_in(|ex| ex.emit(CounterEvent::<T>::TextFocus(true)))
+            .on_focus_out(|ex| ex.emit(CounterEvent::<T>::TextFocus(false)))
+            .toggle_class("invalid", CounterText::error.map(Option::is_some))
+            .class("entry");
+        Self::focus_when(entry, FocusTarget::ValueEntry);
+        // 受け付けなかった理由をテキスト入力の隣に表示する．エラーがない間は隠す
+        Label::new(
+            cx,
+            CounterText::error.map(|error| error.clone().unwrap_or_default()),
+        )
+        .display(CounterText::error.map(Option::is_some))
+        .class("error");
+    }
+
+    fn reset_button(cx: &mut Context) {
+        Button::new(cx, |cx| Label::new(cx, CounterText::reset_label))
+            .on_press(|ex| ex.emit(CounterEvent::<T>::Reset))
+            .role(Role::Button)
+            .name("Reset")
+            .class("reset");
+    }
+}
+// ------------------------------------------
+
+// ------------------------------------------
+// 値の範囲が広いときのために，+1 / +10 / +100のような複数の変化量のボタンを並べるビュー
+// 押されたボタンの変化量をAppEvent::Addで送る
+pub struct MultiStepCounter;
+
+impl View for MultiStepCounter {
+    fn element(&self) -> Option<&'static str> {
+        Some("multi-step-counter")
+    }
+}
+
+impl MultiStepCounter {
+    // stepsに並べる変化量を指定する．それぞれについて-Nと+Nのボタンを作る
+    // 減少のボタンは大きい順に左へ，増加のボタンは小さい順に右へ並ぶ
+    pub fn new<L>(cx: &mut Context, lens: L, steps: Vec<i32>) -> Handle<Self>
+    where
+        L: Lens<Target = i32>,
+    {
+        Self.build(cx, |cx| {
+            HStack::new(cx, |cx| {
+                for &step in steps.iter().rev() {
+                    Self::step_button(cx, -step);
+                }
+                Label::new(cx, lens).class("value");
+                for &step in steps.iter() {
+                    Self::step_button(cx, step);
+                }
+            });
+        })
+    }
+
+    fn step_button(cx: &mut Context, delta: i32) {
+        let label = format!("{:+}", delta);
+        Button::new(cx, |cx| Label::new(cx, &label))
+            .on_press(move |ex| ex.emit(AppEvent::Add(delta)))
+            .role(Role::Button)
+            .name(&label)
+            .class(if delta > 0 { "increment" } else { "decrement" });
+    }
+}
+// ------------------------------------------
+
+// 状態を保存するファイル
+const SAVE_FILE: &str = "counter.json";
+
+fn main() {
+    // アプリケーションを初期化する
+    // クロージャ内でContextを受け取り，ビューを追加していく
+    Application::new(|cx| {
+        // buildメソッドを使用することでアプリケーションに状態を追加する
+        // これによりモデルデータがツリーに組み込まれる．今回の場合root windowに関連付けられる
+        // 前回終了時の状態をファイルから読み込む
+        // 環境変数COUNTER_LOG_EVENTSが設定されている場合は，届いたイベントやCounterからの通知を標準エラー出力に表示する
+        let log_events = std::env::var_os("COUNTER_LOG_EVENTS").is_some();
+        let data = AppData::builder()
+            .min(0)
+            .max(10)
+            .step(1)
+            .on_event(move |event| {
+                if log_events {
+                    eprintln!("AppEvent: {:?}", event);
+                }
+            })
+            .build()
+            .expect("Invalid AppData configuration")
+            .restore(Path::new(SAVE_FILE))
+            // ボタンを押し続けたときに毎回書き込まないように，変化が落ち着いてから保存する
+            .save_delay(cx, Duration::from_millis(500));
+        // モデルに設定した範囲を他のビューにも使用する
+        // countのCounterの範囲とstepはモデルから変更できるため，range_lensとstep_lensでモデルにバインドする
+        let (min, max) = (data.min, data.max);
+        data.build(cx);
+
+        // countの変化を別のスレッドで順に受け取る例
+        // ウィンドウを閉じてCounterが破棄されると送信側もなくなり，forが終了する
+        let (count_sender, count_receiver) = subscription::channel(16);
+        std::thread::spawn(move || {
+            for value in count_receiver {
+                if log_events {
+                    eprintln!("Count changed to {}", value);
+                }
+            }
+        });
+
+        // 別のスレッドで進むダウンロードの件数を想定した値
+        // Counterからの変更も同じAtomicI32に書き込まれるため，どちらで変更しても表示が揃う
+        let downloads = Arc::new(AtomicI32::new(0));
+        let background = downloads.clone();
+        std::thread::spawn(move || loop {
+            std::thread::sleep(Duration::from_secs(1));
+            if background.fetch_add(1, Ordering::Relaxed) >= 99 {
+                break;
+            }
+        });
+
+        // VStackは垂直方向にビューを並べる
+        let root = VStack::new(cx, |cx| {
+            // 数量の選択を想定し，0から10の範囲に制限する
+            Counter::new(cx, AppData::count)
+                // ウィンドウを開いてすぐにキーボードで増加させられるようにする
+                .autofocus(FocusTarget::IncrementButton)
+                .range_lens(AppData::min, AppData::max)
+                .step_lens(AppData::step)
+                .format(|value| format!("Count: {}", value))
+                .disabled_when(AppData::locked)
+                .accessibility_label("Quantity")
+                // Directionボタンで右から左の並びに切り替えられるようにする
+                .bind(AppData::direction, |handle, direction| {
+                    let direction = direction.get(&handle);
+                    handle.direction(direction);
+                })
+                // ボタンを0.8秒以上押し続けてから離すと0に戻す
+                .long_press_reset(true, Duration::from_millis(800))
+                // リセットボタンは3秒以内に2回押したときだけリセットする
+                .confirm_reset(true, Duration::from_secs(3))
+                .tooltips("Add one (↑)", "Subtract one (↓)")
+                // 値が変化したときにLabelを一瞬強調する
+                .animate_on_change(true)
+                // 右クリックでリセットや下限・上限への設定ができるようにする
+                .context_menu(true)
+                // 値が大きくなるにつれて文字の色を緑，黄，赤に変える
+                .thresholds(vec![(0, "level-low"), (4, "level-mid"), (8, "level-high")])
+                // 増加・減少ともに符号付きの変化量としてAddで伝える
+                .on_increment(|cx, amount| cx.emit(AppEvent::Add(amount)))
+                .on_decrement(|cx, amount| cx.emit(AppEvent::Add(-amount)))
+                .on_reset(|cx| cx.emit(AppEvent::Reset))
+                // タッチ操作でも値を直接入力できるように，値をタップすると入力パッドを開く
+                .numpad(true)
+                // テキスト入力に"3+4"のような計算式を入力しても設定できる
+                .allow_expressions(true)
+                .on_set(|cx, value| cx.emit(AppEvent::SetValue(value)))
+                .on_max_reached(move |_| {
+                    if log_events {
+                        eprintln!("Reached the maximum count");
+                    }
+                })
+                // 押し続けている間は出力せず，値が0.5秒変化しなくなってから1回だけ出力する
+                .on_change_debounced(Duration::from_millis(500), move |_, value| {
+                    if log_events {
+                        eprintln!("Count settled at {}", value);
+                    }
+                })
+                .subscribe(count_sender)
+                .on_overflow(move |_, direction| {
+                    if log_events {
+                        match direction {
+                            StepDirection::Increment => {
+                                eprintln!("Count cannot go above the maximum")
+                            }
+                            StepDirection::Decrement => {
+                                eprintln!("Count cannot go below the minimum")
+                            }
+                        }
+                    }
+                })
+                // 実際のアプリではここで種類に応じた音や振動を再生する
+                .on_feedback(move |_, kind| {
+                    if log_events {
+                        eprintln!("Feedback: {:?}", kind);
+                    }
+                });
+
+            // 1 / 10 / 100ずつcountを変化させるボタンの列
+            MultiStepCounter::new(cx, AppData::count, vec![1, 10, 100]);
+
+            // 同じcountを表示するだけのCounter
+            // Labelの幅は文字列に合わせて変わるため，"Total: 100"までの幅を確保して中央の位置がずれないようにする
+            Counter::display(cx, AppData::count)
+                .format(|value| format!("Total: {}", value))
+                .value_width(10)
+                .leading_icon(|cx| {
+                    Label::new(cx, "♥").class("heart");
+                });
+
+            // 同じcountを操作する小さなCounter
+            let pill = Counter::pill(cx, AppData::count)
+                .range(min, max)
+                // 小さなボタンで誤って2回押さないように，0.2秒以内の押下は無視する
+                .cooldown(Duration::from_millis(200))
+                // 値をダブルクリックすると，その場で入力して変更できる
+                .inline_edit(true)
+                // 直近5秒間にどれだけ変化したかを下に表示する
+                .show_rate(Duration::from_secs(5))
+                .on_set(|cx, value| cx.emit(AppEvent::SetValue(value)))
+                .step_lens(AppData::step)
+                .disabled_when(AppData::locked)
+                .on_increment(|cx, amount| cx.emit(AppEvent::Add(amount)))
+                .on_decrement(|cx, amount| cx.emit(AppEvent::Add(-amount)))
+                .entity();
+
+            // 別のボタンから小さなCounterを1回押したときと同じように操作する
+            // Counterのcooldownや範囲の判定を通るため，上限に達している場合は増えない
+            Button::new(cx, |cx| Label::new(cx, "Nudge"))
+                .on_press(move |ex| Counter::<i32>::emit_increment(ex, pill))
+                .class("nudge");
+
+            // ボタンより大まかに操作するためのスライダー
+            // 同じcountにバインドしているため，ボタンで変更してもスライダーで変更しても両方の表示が揃う
+            // stepは画面から変更できるため，変更されたときに作り直してstep単位で動くようにする
+            Binding::new(cx, AppData::step, move |cx, step| {
+                let step = step.get(cx);
+                Slider::new(cx, AppData::count.map(|count| *count as f32))
+                    .range(min as f32..max as f32)
+                    .step(step as f32)
+                    // モデルでも範囲内に収められるため，スライダーの外の値が設定されることはない
+                    // ドラッグ中の値は履歴に積まず，離したときに1回の変更として積む
+                    .on_changing(|cx, value| cx.emit(AppEvent::DragValue(value.round() as i32)))
+                    .on_mouse_up(|cx, _| cx.emit(AppEvent::EndDrag))
+                    .class("count-slider");
+            });
+
+            // countが上限の10に対してどれだけ進んでいるかを表示する
+            ProgressBar::new(cx, AppData::count, max);
+
+            // 目標の5に達すると色が変わり，チェックマークが表示される
+            HStack::new(cx, |cx| {
+                Label::new(cx, "Goal: 5").class("kind");
+                Counter::pill(cx, AppData::count)
+                    // 上の小さなCounterと見た目を変えるため，このCounterだけにスタイルを適用する
+                    .style_scope("goal")
+                    .range(min, max)
+                    .target(5)
+                    .on_increment(|cx, amount| cx.emit(AppEvent::Add(amount)))
+                    .on_decrement(|cx, amount| cx.emit(AppEvent::Add(-amount)));
+                Label::new(cx, "\u{2713}")
+                    .display(Counter::reached_lens(AppData::count, 5))
+                    .class("checkmark");
+            })
+            .class("row");
+
+            // countが大きくなるほど背景が濃くなる帯
+            Element::new(cx)
+                .opacity(Counter::ratio_lens(AppData::count, min, max))
+                .class("gauge");
+
+            HStack::new(cx, |cx| {
+                Checkbox::new(cx, AppData::locked).on_toggle(|cx| cx.emit(AppEvent::ToggleLocked));
+                Label::new(cx, "Lock");
+                // 履歴が空の場合はボタンを無効化する
+                // mapを使用することでLensの値から別の値を導出することができる
+                Button::new(cx, |cx| Label::new(cx, "Undo"))
+                    .on_press(|ex| ex.emit(AppEvent::Undo))
+                    .disabled(AppData::history.map(|history| history.is_empty()));
+                Button::new(cx, |cx| Label::new(cx, "Redo"))
+                    .on_press(|ex| ex.emit(AppEvent::Redo))
+                    .disabled(AppData::redo.map(|redo| redo.is_empty()));
+                // countが読み込んだ値から変更されていない間は保存ボタンを無効化する
+                Button::new(cx, |cx| Label::new(cx, "Save"))
+                    .on_press(|ex| ex.emit(AppEvent::Save))
+                    .disabled(AppData::dirty.map(|dirty| !dirty));
+                Button::new(cx, |cx| Label::new(cx, "Theme"))
+                    .on_press(|ex| ex.emit(AppEvent::ToggleTheme));
+                Button::new(cx, |cx| Label::new(cx, "Direction"))
+                    .on_press(|ex| ex.emit(AppEvent::ToggleDirection));
+                // countが0より大きい間は件数のBadgeを表示する
+                Button::new(cx, |cx| {
+                    HStack::new(cx, |cx| {
+                        Label::new(cx, "Items");
+                        Badge::with_cap(cx, AppData::count, 9);
+                    })
+                    .class("badge-row")
+                })
+                .on_press(|ex| ex.emit(AppEvent::Reset));
+                Button::new(cx, |cx| Label::new(cx, "Display"))
+                    .on_press(|ex| ex.emit(AppEvent::OpenDisplayWindow))
+                    .disabled(AppData::display_window);
+            })
+            .class("row");
+
+            // 2つ目のウィンドウもメインのウィンドウと同じツリーに作られるため，
+            // 同じAppDataにバインドでき，どちらのウィンドウで変更しても両方の表示が更新される
+            Binding::new(cx, AppData::display_window, |cx, open| {
+                if open.get(cx) {
+                    let window = Window::new(cx, |cx| {
+                        Label::new(cx, AppData::count).class("big-count");
+                    })
+                    .on_close(|cx| cx.emit(AppEvent::CloseDisplayWindow))
+                    .title("Count display")
+                    .inner_size((300, 200));
+                    apply_theme(window);
+                }
+            });
+
+            // 表の1行を想定し，品名と単価の隣に小さなCounterを置く
+            HStack::new(cx, |cx| {
+                Label::new(cx, "Apples").class("cell");
+                Label::new(cx, "$1.20").class("cell");
+                Counter::compact(cx, AppData::count)
+                    .range(min, max)
+                    .on_increment(|cx, amount| cx.emit(AppEvent::Add(amount)))
+                    .on_decrement(|cx, amount| cx.emit(AppEvent::Add(-amount)));
+            })
+            .class("table-row");
+
+            // 減少ボタン・値・増加ボタンの3通りの並べ方
+            for layout in [
+                CounterLayout::MinusValuePlus,
+                CounterLayout::ValueThenButtons,
+                CounterLayout::ButtonsThenValue,
+            ] {
+                Counter::pill(cx, AppData::count)
+                    .layout(layout)
+                    .range(min, max)
+                    .on_increment(|cx, amount| cx.emit(AppEvent::Add(amount)))
+                    .on_decrement(|cx, amount| cx.emit(AppEvent::Add(-amount)));
+            }
+
+            // 利用者ごとのブランドの色を想定して，ボタンの色だけを変えたCounterを並べる
+            HStack::new(cx, |cx| {
+                for color in [Color::rgb(0x1d, 0x9b, 0xf0), Color::rgb(0xe0, 0x24, 0x5e)] {
+                    Counter::pill(cx, AppData::count)
+                        .range(min, max)
+                        .accent_color(color)
+                        .on_increment(|cx, amount| cx.emit(AppEvent::Add(amount)))
+                        .on_decrement(|cx, amount| cx.emit(AppEvent::Add(-amount)));
+                }
+            })
+            .class("row");
+
+            // f64を扱うCounter．0.5kgずつ変化させる
+            // サイドバーのような狭い場所を想定して縦に並べる
+            Counter::<f64>::new(cx, AppData::weight_lens())
+                .orientation(CounterOrientation::Vertical)
+                // 縦に並べるため，ボタンには文字の代わりに上下の矢印を表示する
+                .increment_content(|cx| {
+                    Label::new(cx, "\u{25B2}").class("arrow");
+                })
+                .decrement_content(|cx| {
+                    Label::new(cx, "\u{25BC}").class("arrow");
+                })
+                .wheel(true)
+                // 押し続けるほど速く変化させる
+                .repeat_acceleration(0.9, Duration::from_millis(20))
+                // countのCounterと区別できるように別のクラスで色を変える
+                .count_class("weight")
+                // 0.5kg単位のため小数点以下1桁まで表示する
+                .precision(1)
+                // ドイツ語の書式で表示する例．Labelでは小数点がカンマになる
+                .locale(Locale::DeDe)
+                .step(0.5)
+                // Shiftキーを押しながら操作すると5kgずつ変化させる
+                .shift_multiplier(10)
+                .range(0.0, 100.0)
+                // 入力した値は0.5kg単位に丸める
+                .snap(true)
+                // Enterを押したときだけ確定し，入力途中でフォーカスが外れた場合は元に戻す
+                .commit_mode(CommitMode::OnEnter)
+                .on_increment(|cx, amount| cx.emit(AppEvent::AdjustWeight(amount)))
+                .on_decrement(|cx, amount| cx.emit(AppEvent::AdjustWeight(-amount)))
+                .on_set(|cx, value| cx.emit(AppEvent::SetWeight(value)));
+
+            // countのCounterのstepを変更する
+            HStack::new(cx, |cx| {
+                Label::new(cx, "Step").class("kind");
+                Spinner::new(cx, AppData::step)
+                    .range(1, 5)
+                    .on_increment(|cx, amount| cx.emit(AppEvent::AddStep(amount)))
+                    .on_decrement(|cx, amount| cx.emit(AppEvent::AddStep(-amount)));
+            })
+            .class("row");
+
+            HStack::new(cx, |cx| {
+                Label::new(cx, "Downloads").class("kind");
+                Counter::from_atomic(cx, downloads.clone()).range(0, 100);
+            })
+            .class("row");
+
+            // フォーム向けのSpinner．Counterのモディファイアをそのまま使用できる
+            HStack::new(cx, |cx| {
+                Label::new(cx, "Rooms").class("kind");
+                Spinner::new(cx, AppData::rooms)
+                    .range(ROOMS_RANGE.0, ROOMS_RANGE.1)
+                    // 部屋数を変えると料金が計算し直されるため，押している間は変更後の値を見せるだけにする
+                    .preview_on_hold(true)
+                    .on_increment(|cx, amount| cx.emit(AppEvent::AddRooms(amount)))
+                    .on_decrement(|cx, amount| cx.emit(AppEvent::RemoveRooms(amount)));
+            })
+            .class("row");
+
+            // 23時で増加させると0時に，0時で減少させると23時に戻る
+            // 上限・下限から反対側の端へ移るときはon_setで値が設定される
+            HStack::new(cx, |cx| {
+                Label::new(cx, "Hour").class("kind");
+                Spinner::new(cx, AppData::hour)
+                    .range(0, 23)
+                    .wrap(true)
+                    .on_increment(|cx, amount| cx.emit(AppEvent::AdjustHour(amount)))
+                    .on_decrement(|cx, amount| cx.emit(AppEvent::AdjustHour(-amount)))
+                    .on_set(|cx, value| cx.emit(AppEvent::SetHour(value)));
+            })
+            .class("row");
+
+            // 区分ごとに独立したCounterを並べる
+            // 各Counterはcountsの自分の要素だけを更新する
+            for (index, kind) in GUEST_KINDS.iter().enumerate() {
+                HStack::new(cx, |cx| {
+                    Label::new(cx, *kind).class("kind");
+                    // AppDataのslotsに登録された番号を使うことで，どのCounterも同じコールバックを使える
+                    Counter::new_with_id(cx, AppData::count_at(index), guest_id(index))
+                        .range(GUEST_RANGE.0, GUEST_RANGE.1)
+                        // "1 guest"，"2 guests"のように人数に単位を付けて表示する
+                        .unit("guest", "guests")
+                        // 大人は2人で予約することが多いため，リセットすると2人に戻す
+                        .default_value(if index == 0 { 2 } else { 0 })
+                        .on_step(|cx, id, direction, amount| {
+                            cx.emit(AppEvent::Step(id, direction, amount))
+                        })
+                        .on_set(move |cx, value| cx.emit(AppEvent::SetAt(index, value)));
+                })
+                .class("row");
+            }
+
+            // 区分ごとのCounterの合計．どの区分を変更しても更新される
+            HStack::new(cx, |cx| {
+                Label::new(cx, "Total").class("kind");
+                Label::new(
+                    cx,
+                    AppData::counts_total().map(|total| format!("{} guests", total)),
+                )
+                .class("count");
+                // 大人2人だけの状態にまとめて戻す
+                Button::new(cx, |cx| Label::new(cx, "Preset"))
+                    .on_press(|cx| cx.emit(AppEvent::SetAll(vec![2, 0, 0])));
+            })
+            .class("row");
+        });
+        apply_theme(root);
+
+        // アプリケーションにスタイルを適用する
+        cx.add_stylesheet(include_style!("src/style.css"))
+            .expect("Failed to load stylesheet");
+        cx.add_stylesheet(include_style!("src/light.css"))
+            .expect("Failed to load stylesheet");
+        cx.add_stylesheet(include_style!("src/dark.css"))
             .expect("Failed to load stylesheet");
     })
     .title("Counter")
-    .inner_size((800, 300))
+    .inner_size((800, 600))
     .run()
     .unwrap();
 }
+
+#[cfg(test)]
+mod tests {
+    use std::cell::{Cell, RefCell};
+
+    use super::*;
+    use crate::testing::CounterHarness;
+
+    #[test]
+    fn repeat_ticks_follow_the_mock_clock() {
+        let mut harness = CounterHarness::with_counter(AppData::default(), |counter| {
+            counter.repeat(Duration::from_millis(400), Duration::from_millis(100))
+        });
+        // 押した瞬間に1回変化し，repeat_delayが経つまでは繰り返さない
+        harness.send_counter(CounterEvent::StartRepeat(StepDirection::Increment));
+        assert_eq!(harness.count(), 1);
+        harness.advance(Duration::from_millis(399));
+        assert_eq!(harness.count(), 1);
+        harness.advance(Duration::from_millis(1));
+        assert_eq!(harness.count(), 2);
+        // その後はrepeat_intervalごとに1回ずつ変化する．まとめて進めても経過した分だけ繰り返す
+        harness.advance(Duration::from_millis(350));
+        assert_eq!(harness.count(), 5);
+        harness.send_counter(CounterEvent::StopRepeat);
+        harness.advance(Duration::from_secs(1));
+        assert_eq!(harness.count(), 5);
+    }
+
+    #[test]
+    fn repeat_acceleration_shortens_the_interval() {
+        let mut harness = CounterHarness::with_counter(AppData::default(), |counter| {
+            counter
+                .repeat(Duration::from_millis(400), Duration::from_millis(100))
+                .repeat_acceleration(0.5, Duration::from_millis(25))
+        });
+        harness.send_counter(CounterEvent::StartRepeat(StepDirection::Increment));
+        // 400msで1回目の繰り返し，その後は100ms，50ms，25ms，25ms...の間隔になる
+        harness.advance(Duration::from_millis(400));
+        assert_eq!(harness.count(), 2);
+        harness.advance(Duration::from_millis(150));
+        assert_eq!(harness.count(), 4);
+        harness.advance(Duration::from_millis(50));
+        assert_eq!(harness.count(), 6);
+    }
+
+    #[test]
+    fn stops_at_both_ends_of_the_range() {
+        let data = AppData::builder().min(0).max(3).build().unwrap();
+        let mut harness = CounterHarness::new(data);
+        for _ in 0..5 {
+            harness.increment();
+        }
+        assert_eq!(harness.count(), 3);
+        for _ in 0..5 {
+            harness.decrement();
+        }
+        assert_eq!(harness.count(), 0);
+    }
+
+    #[test]
+    fn saturates_a_step_that_overshoots_the_bound() {
+        let data = AppData::builder().min(0).max(10).step(4).build().unwrap();
+        let mut harness = CounterHarness::new(data);
+        for _ in 0..3 {
+            harness.increment();
+        }
+        // 8から4増やすと上限を超えるため，残りの2だけ変化させる
+        assert_eq!(harness.count(), 10);
+    }
+
+    #[test]
+    fn undo_and_redo_walk_the_history() {
+        let mut harness = CounterHarness::new(AppData::default());
+        for _ in 0..3 {
+            harness.increment();
+        }
+        assert_eq!(harness.snapshot().history, vec![0, 1, 2]);
+
+        harness.send_app(AppEvent::Undo);
+        harness.send_app(AppEvent::Undo);
+        let snapshot = harness.snapshot();
+        assert_eq!(snapshot.count, 1);
+        assert_eq!(snapshot.history, vec![0]);
+        assert_eq!(snapshot.redo, vec![3, 2]);
+
+        harness.send_app(AppEvent::Redo);
+        let snapshot = harness.snapshot();
+        assert_eq!(snapshot.count, 2);
+        assert_eq!(snapshot.history, vec![0, 1]);
+        assert_eq!(snapshot.redo, vec![3]);
+
+        // 新しい変更を行うと，やり直しの履歴は破棄される
+        harness.increment();
+        assert!(harness.snapshot().redo.is_empty());
+    }
+
+    #[test]
+    fn arrow_keys_step_the_model() {
+        let mut harness = CounterHarness::new(AppData::default());
+        harness.key_down(Code::ArrowUp);
+        harness.key_down(Code::ArrowUp);
+        harness.key_down(Code::ArrowDown);
+        assert_eq!(harness.count(), 1);
+    }
+
+    #[test]
+    fn keyboard_can_be_disabled() {
+        let mut harness =
+            CounterHarness::with_counter(AppData::default(), |counter| counter.keyboard(false));
+        harness.key_down(Code::ArrowUp);
+        assert_eq!(harness.count(), 0);
+    }
+
+    // テストごとに異なる一時ファイルのパスを返す
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("vizia-lab-{}-{}.json", name, std::process::id()))
+    }
+
+    #[test]
+    fn save_and_load_round_trip() {
+        let path = temp_path("round-trip");
+        let data = AppData {
+            count: 7,
+            weight_scaled: to_scaled(2.5),
+            counts: vec![2, 1, 0],
+            ..AppData::default()
+        };
+        data.save(&path).unwrap();
+        let loaded = AppData::load(&path);
+        fs::remove_file(&path).unwrap();
+        assert_eq!(loaded.count, 7);
+        assert_eq!(loaded.weight(), 2.5);
+        assert_eq!(loaded.counts, vec![2, 1, 0]);
+        // 読み込んだ値は変更されていない状態として扱う
+        assert!(!loaded.dirty);
+    }
+
+    #[test]
+    fn load_falls_back_to_defaults() {
+        let missing = AppData::load(&temp_path("missing"));
+        assert_eq!(missing.count, 0);
+
+        let path = temp_path("corrupt");
+        fs::write(&path, "not json").unwrap();
+        let corrupt = AppData::load(&path);
+        fs::remove_file(&path).unwrap();
+        assert_eq!(corrupt.count, 0);
+        assert_eq!(corrupt.counts, vec![0; GUEST_KINDS.len()]);
+    }
+
+    #[test]
+    fn on_change_receives_the_updated_values() {
+        let values = Rc::new(RefCell::new(Vec::new()));
+        let received = values.clone();
+        let mut harness = CounterHarness::with_counter(AppData::default(), move |counter| {
+            counter.on_change(move |_, value| received.borrow_mut().push(value))
+        });
+        harness.increment();
+        harness.increment();
+        harness.decrement();
+        // 構築時の通知では値が変わらないため呼び出されない
+        assert_eq!(*values.borrow(), vec![1, 2, 1]);
+    }
+
+    #[test]
+    fn text_entry_sets_the_model() {
+        let mut harness = CounterHarness::new(AppData::default());
+        harness.send_counter(CounterEvent::SubmitEntry(String::from("42"), true));
+        assert_eq!(harness.count(), 42);
+    }
+
+    #[test]
+    fn text_entry_ignores_garbage() {
+        let mut harness = CounterHarness::new(AppData::builder().count(5).build().unwrap());
+        harness.send_counter(CounterEvent::SubmitEntry(String::from("abc"), true));
+        assert_eq!(harness.count(), 5);
+    }
+
+    #[test]
+    fn increments_only_the_indexed_counter() {
+        let mut harness = CounterHarness::new(AppData::default());
+        harness.send_app(AppEvent::IncrementAt(1, 1));
+        harness.send_app(AppEvent::IncrementAt(1, 1));
+        assert_eq!(harness.snapshot().counts, vec![0, 2, 0]);
+        assert_eq!(AppData::count_at(1).get(harness.context()), 2);
+    }
+
+    #[test]
+    fn disabled_counter_ignores_presses() {
+        let data = AppData {
+            locked: true,
+            ..AppData::default()
+        };
+        let mut harness =
+            CounterHarness::with_counter(data, |counter| counter.disabled_when(AppData::locked));
+        harness.increment();
+        harness.key_down(Code::ArrowUp);
+        assert_eq!(harness.count(), 0);
+
+        // 有効に戻すと再び受け付ける
+        harness.send_app(AppEvent::ToggleLocked);
+        harness.increment();
+        assert_eq!(harness.count(), 1);
+    }
+
+    #[test]
+    fn long_press_resets_instead_of_stepping() {
+        let data = AppData::builder().count(5).build().unwrap();
+        let mut harness = CounterHarness::with_counter(data, |counter| {
+            counter.long_press_reset(true, Duration::from_millis(800))
+        });
+        harness.send_counter(CounterEvent::StartRepeat(StepDirection::Increment));
+        // 押している間は変化させない
+        harness.advance(Duration::from_millis(900));
+        assert_eq!(harness.count(), 5);
+        harness.send_counter(CounterEvent::StopRepeat);
+        assert_eq!(harness.count(), 0);
+    }
+
+    #[test]
+    fn quick_tap_still_steps_with_long_press_reset() {
+        let data = AppData::builder().count(5).build().unwrap();
+        let mut harness = CounterHarness::with_counter(data, |counter| {
+            counter.long_press_reset(true, Duration::from_millis(800))
+        });
+        harness.send_counter(CounterEvent::StartRepeat(StepDirection::Increment));
+        harness.advance(Duration::from_millis(100));
+        harness.send_counter(CounterEvent::StopRepeat);
+        // on_pressとon_mouse_upの両方からStopRepeatが届いても1回だけ変化する
+        harness.send_counter(CounterEvent::StopRepeat);
+        assert_eq!(harness.count(), 6);
+    }
+
+    #[test]
+    fn shift_multiplies_the_step() {
+        let mut harness = CounterHarness::with_counter(AppData::default(), |counter| {
+            counter.shift_multiplier(10)
+        });
+        harness.set_modifiers(Modifiers::SHIFT);
+        harness.increment();
+        assert_eq!(harness.count(), 10);
+        harness.set_modifiers(Modifiers::empty());
+        harness.increment();
+        assert_eq!(harness.count(), 11);
+    }
+
+    #[test]
+    fn bound_callbacks_fire_on_the_transition_only() {
+        let min_hits = Rc::new(Cell::new(0));
+        let max_hits = Rc::new(Cell::new(0));
+        let (on_min, on_max) = (min_hits.clone(), max_hits.clone());
+        let data = AppData::builder().min(0).max(2).build().unwrap();
+        let mut harness = CounterHarness::with_counter(data, move |counter| {
+            counter
+                .on_min_reached(move |_| on_min.set(on_min.get() + 1))
+                .on_max_reached(move |_| on_max.set(on_max.get() + 1))
+        });
+        // 上限に達した後の押下では呼び出されない
+        for _ in 0..3 {
+            harness.increment();
+        }
+        assert_eq!(max_hits.get(), 1);
+        for _ in 0..3 {
+            harness.decrement();
+        }
+        assert_eq!(min_hits.get(), 1);
+        harness.increment();
+        harness.increment();
+        assert_eq!((min_hits.get(), max_hits.get()), (1, 2));
+    }
+
+    #[test]
+    fn rooms_stay_within_their_range() {
+        let mut harness = CounterHarness::new(AppData::default());
+        harness.send_app(AppEvent::RemoveRooms(3));
+        assert_eq!(harness.snapshot().rooms, ROOMS_RANGE.0);
+        harness.send_app(AppEvent::AddRooms(u32::MAX));
+        assert_eq!(harness.snapshot().rooms, ROOMS_RANGE.1);
+    }
+
+    #[test]
+    fn toggle_theme_flips_the_variant() {
+        let mut harness = CounterHarness::new(AppData::default());
+        assert_eq!(harness.snapshot().theme, Theme::Light);
+        harness.send_app(AppEvent::ToggleTheme);
+        assert_eq!(harness.snapshot().theme, Theme::Dark);
+        harness.send_app(AppEvent::ToggleTheme);
+        assert_eq!(harness.snapshot().theme, Theme::Light);
+    }
+
+    #[test]
+    fn builder_rejects_an_inverted_range() {
+        let result = AppData::builder().min(5).max(1).build();
+        assert_eq!(
+            result.err(),
+            Some(AppDataError::InvalidRange { min: 5, max: 1 })
+        );
+    }
+
+    #[test]
+    fn builder_rejects_a_non_positive_step() {
+        let result = AppData::builder().step(0).build();
+        assert_eq!(result.err(), Some(AppDataError::InvalidStep(0)));
+    }
+
+    #[test]
+    fn builder_clamps_the_initial_count() {
+        let data = AppData::builder()
+            .count(20)
+            .min(0)
+            .max(10)
+            .step(2)
+            .build()
+            .unwrap();
+        assert_eq!((data.count, data.min, data.max, data.step), (10, 0, 10, 2));
+        assert_eq!(data.initial, 10);
+    }
+
+    #[test]
+    fn snap_to_step_rounds_to_the_nearest_multiple() {
+        assert_eq!(snap_to_step(12, 5), 10);
+        assert_eq!(snap_to_step(13, 5), 15);
+        assert_eq!(snap_to_step(-12, 5), -10);
+        assert_eq!(snap_to_step(-13, 5), -15);
+        assert_eq!(snap_to_step(4, 3), 3);
+        assert_eq!(snap_to_step(5, 3), 6);
+        assert_eq!(snap_to_step(-5, 3), -6);
+        assert_eq!(snap_to_step(-4, 3), -3);
+    }
+
+    #[test]
+    fn snap_to_step_rounds_half_up() {
+        assert_eq!(snap_to_step(12.5, 5.0), 15.0);
+        assert_eq!(snap_to_step(-12.5, 5.0), -10.0);
+        assert_eq!(snap_to_step(1.5, 3.0), 3.0);
+    }
+
+    #[test]
+    fn snap_applies_to_text_entry_only() {
+        let data = AppData::builder().min(0).max(23).step(5).build().unwrap();
+        let mut harness = CounterHarness::with_counter(data, |counter| counter.snap(true));
+        harness.send_counter(CounterEvent::SubmitEntry(String::from("12"), true));
+        assert_eq!(harness.count(), 10);
+        // 上限への移動は丸めずに上限そのものに設定する
+        harness.key_down(Code::End);
+        assert_eq!(harness.count(), 23);
+        harness.key_down(Code::Home);
+        assert_eq!(harness.count(), 0);
+    }
+
+    #[test]
+    fn on_overflow_fires_for_every_rejected_press() {
+        let overflows = Rc::new(RefCell::new(Vec::new()));
+        let received = overflows.clone();
+        let data = AppData::builder().count(3).min(0).max(3).build().unwrap();
+        let mut harness = CounterHarness::with_counter(data, move |counter| {
+            counter.on_overflow(move |_, direction| received.borrow_mut().push(direction))
+        });
+        for _ in 0..5 {
+            harness.increment();
+        }
+        assert_eq!(harness.count(), 3);
+        assert_eq!(*overflows.borrow(), vec![StepDirection::Increment; 5]);
+    }
+
+    #[test]
+    fn debounced_change_fires_once_with_the_final_value() {
+        let settled = Rc::new(RefCell::new(Vec::new()));
+        let received = settled.clone();
+        let mut harness = CounterHarness::with_counter(AppData::default(), move |counter| {
+            counter.on_change_debounced(Duration::from_millis(300), move |_, value| {
+                received.borrow_mut().push(value)
+            })
+        });
+        for _ in 0..3 {
+            harness.increment();
+            harness.advance(Duration::from_millis(50));
+        }
+        assert!(settled.borrow().is_empty());
+        // 最後の変化から300ms経つと1回だけ呼び出される
+        harness.advance(Duration::from_millis(250));
+        harness.advance(Duration::from_secs(1));
+        assert_eq!(*settled.borrow(), vec![3]);
+    }
+
+    #[test]
+    fn blur_commits_according_to_the_commit_mode() {
+        for (mode, expected) in [
+            (CommitMode::OnEnter, 0),
+            (CommitMode::OnBlur, 7),
+            (CommitMode::OnEnterOrBlur, 7),
+        ] {
+            let mut harness = CounterHarness::with_counter(AppData::default(), |counter| {
+                counter.commit_mode(mode)
+            });
+            // falseはEnterではなくフォーカスが外れて確定されたことを表す
+            harness.send_counter(CounterEvent::SubmitEntry(String::from("7"), false));
+            assert_eq!(harness.count(), expected);
+        }
+    }
+
+    #[test]
+    fn enter_is_ignored_when_committing_on_blur() {
+        let mut harness = CounterHarness::with_counter(AppData::default(), |counter| {
+            counter.commit_mode(CommitMode::OnBlur)
+        });
+        harness.send_counter(CounterEvent::SubmitEntry(String::from("7"), true));
+        assert_eq!(harness.count(), 0);
+    }
+
+    #[test]
+    fn step_routes_to_the_slot_of_the_sending_counter() {
+        let mut harness = CounterHarness::with_view(AppData::default(), |cx, _| {
+            let on_step = |cx: &mut EventContext, id, direction, amount| {
+                cx.emit(AppEvent::Step(id, direction, amount))
+            };
+            Counter::new_with_id(cx, AppData::count_at(0), guest_id(0)).on_step(on_step);
+            Counter::new_with_id(cx, AppData::count_at(2), guest_id(2))
+                .on_step(on_step)
+                .entity()
+        });
+        harness.increment();
+        harness.increment();
+        assert_eq!(harness.snapshot().counts, vec![0, 0, 2]);
+    }
+
+    #[test]
+    fn step_from_an_unknown_counter_is_ignored() {
+        let mut harness = CounterHarness::new(AppData::default());
+        harness.send_app(AppEvent::Step(CounterId(99), StepDirection::Increment, 1));
+        assert_eq!(harness.snapshot().counts, vec![0; GUEST_KINDS.len()]);
+    }
+
+    #[test]
+    fn confirm_reset_needs_a_second_press() {
+        let data = AppData::builder().count(5).build().unwrap();
+        let mut harness = CounterHarness::with_counter(data, |counter| {
+            counter.confirm_reset(true, Duration::from_secs(3))
+        });
+        harness.send_counter(CounterEvent::Reset);
+        assert_eq!(harness.count(), 5);
+        assert_eq!(harness.reset_label(), "Sure?");
+        harness.send_counter(CounterEvent::Reset);
+        assert_eq!(harness.count(), 0);
+        assert_eq!(harness.reset_label(), "Reset");
+    }
+
+    #[test]
+    fn confirm_reset_disarms_after_the_timeout() {
+        let data = AppData::builder().count(5).build().unwrap();
+        let mut harness = CounterHarness::with_counter(data, |counter| {
+            counter.confirm_reset(true, Duration::from_secs(3))
+        });
+        harness.send_counter(CounterEvent::Reset);
+        // 確認待ちの時間が経過するとタイマーからConfirmTimeoutが届く
+        harness.send_counter(CounterEvent::ConfirmTimeout);
+        assert_eq!(harness.reset_label(), "Reset");
+        // 取り消された後の押下は再び1回目として扱われる
+        harness.send_counter(CounterEvent::Reset);
+        assert_eq!(harness.count(), 5);
+    }
+
+    #[test]
+    fn long_press_reset_also_asks_for_confirmation() {
+        let data = AppData::builder().count(5).build().unwrap();
+        let mut harness = CounterHarness::with_counter(data, |counter| {
+            counter
+                .long_press_reset(true, Duration::from_millis(800))
+                .confirm_reset(true, Duration::from_secs(3))
+        });
+        harness.send_counter(CounterEvent::StartRepeat(StepDirection::Increment));
+        harness.advance(Duration::from_secs(1));
+        harness.send_counter(CounterEvent::StopRepeat);
+        assert_eq!(harness.count(), 5);
+        assert_eq!(harness.reset_label(), "Sure?");
+        harness.send_counter(CounterEvent::Reset);
+        assert_eq!(harness.count(), 0);
+    }
+
+    #[test]
+    fn set_count_clamps_to_the_configured_range() {
+        let mut data = AppData::builder().min(0).max(10).build().unwrap();
+        data.set_count(999);
+        assert_eq!(data.count, 10);
+        data.set_count(-5);
+        assert_eq!(data.count, 0);
+        // イベントによる変更と同じように元に戻せる
+        assert_eq!(data.history, vec![0, 10]);
+        assert!(!data.dirty);
+    }
+
+    #[test]
+    fn state_snapshot_reports_the_counter() {
+        let data = AppData::builder().min(0).max(10).build().unwrap();
+        let mut harness = CounterHarness::with_counter(data, |counter| {
+            counter.format(|value| format!("Count: {}", value))
+        });
+        harness.increment();
+        harness.increment();
+        let state = harness.state();
+        assert_eq!(
+            state,
+            CounterState {
+                value: 2,
+                min: Some(0),
+                max: Some(10),
+                disabled: false,
+                display: String::from("Count: 2"),
+            }
+        );
+        let json = serde_json::to_value(&state).unwrap();
+        assert_eq!(json["value"], 2);
+        assert_eq!(json["display"], "Count: 2");
+    }
+
+    #[test]
+    fn cooldown_ignores_presses_that_come_too_soon() {
+        let data = AppData::builder().max(10).build().unwrap();
+        let mut harness = CounterHarness::with_counter(data, |counter| {
+            counter.cooldown(Duration::from_millis(200))
+        });
+        harness.increment();
+        // 前回の押下から20msしか経っていないため無視される
+        harness.advance(Duration::from_millis(20));
+        harness.increment();
+        assert_eq!(harness.count(), 1);
+        // 前回受け付けた押下から200ms経てば受け付ける
+        harness.advance(Duration::from_millis(180));
+        harness.increment();
+        assert_eq!(harness.count(), 2);
+    }
+
+    #[test]
+    fn subscribe_receives_every_change() {
+        let (sender, receiver) = subscription::channel(2);
+        let data = AppData::builder().max(10).build().unwrap();
+        let mut harness = CounterHarness::with_counter(data, |counter| counter.subscribe(sender));
+        harness.increment();
+        harness.increment();
+        harness.increment();
+        // 容量を超えた分は古い値から捨てられる
+        assert_eq!(receiver.try_recv(), Some(2));
+        assert_eq!(receiver.try_recv(), Some(3));
+        assert_eq!(receiver.try_recv(), None);
+    }
+
+    #[test]
+    fn focus_callbacks_ignore_moves_inside_the_counter() {
+        let focused = Rc::new(Cell::new(0));
+        let blurred = Rc::new(Cell::new(0));
+        let (on_focus, on_blur) = (focused.clone(), blurred.clone());
+        let mut harness = CounterHarness::with_counter(AppData::default(), move |counter| {
+            counter
+                .on_focus(move |_| on_focus.set(on_focus.get() + 1))
+                .on_blur(move |_| on_blur.set(on_blur.get() + 1))
+        });
+        let counter = harness.counter();
+        harness.context().emit_to(counter, WindowEvent::FocusIn);
+        harness.flush();
+        assert_eq!((focused.get(), blurred.get()), (1, 0));
+
+        // 内部のボタンから別のボタンへ移るときは，FocusOutの直後にFocusInが届く
+        harness.context().emit_to(counter, WindowEvent::FocusOut);
+        harness.context().emit_to(counter, WindowEvent::FocusIn);
+        harness.flush();
+        assert_eq!((focused.get(), blurred.get()), (1, 0));
+
+        harness.context().emit_to(counter, WindowEvent::FocusOut);
+        harness.flush();
+        assert_eq!((focused.get(), blurred.get()), (1, 1));
+    }
+
+    #[test]
+    fn counter_config_round_trips_through_json() {
+        let config = CounterConfig {
+            step: Some(2),
+            min: Some(-4),
+            max: Some(8),
+            orientation: CounterOrientation::Vertical,
+            class: Some(String::from("compact")),
+        };
+        let json = serde_json::to_string(&config).unwrap();
+        assert_eq!(
+            serde_json::from_str::<CounterConfig<i32>>(&json).unwrap(),
+            config
+        );
+    }
+
+    #[test]
+    fn counter_config_fills_in_missing_fields() {
+        let config: CounterConfig<i32> = serde_json::from_str(r#"{"max": 3}"#).unwrap();
+        assert_eq!(
+            config,
+            CounterConfig {
+                max: Some(3),
+                ..CounterConfig::default()
+            }
+        );
+    }
+
+    #[test]
+    fn from_config_applies_the_range_and_step() {
+        let config = CounterConfig {
+            step: Some(2),
+            min: Some(0),
+            max: Some(3),
+            ..CounterConfig::default()
+        };
+        let mut harness = CounterHarness::with_view(AppData::default(), |cx, clock| {
+            Counter::from_config(cx, AppData::count, &config)
+                .clock(clock)
+                .on_increment(|ex, amount| ex.emit(AppEvent::Add(amount)))
+                .entity()
+        });
+        harness.increment();
+        assert_eq!(harness.count(), 2);
+        // 上限を超える分は飽和する
+        harness.increment();
+        assert_eq!(harness.count(), 3);
+        assert_eq!(harness.state().max, Some(3));
+    }
+
+    #[test]
+    fn overflow_policy_add_and_sub() {
+        assert_eq!(OverflowPolicy::Saturate.add(i32::MAX, 1), Some(i32::MAX));
+        assert_eq!(OverflowPolicy::Wrap.add(i32::MAX, 1), Some(i32::MIN));
+        assert_eq!(OverflowPolicy::Ignore.add(i32::MAX, 1), None);
+        assert_eq!(OverflowPolicy::Saturate.sub(i32::MIN, 1), Some(i32::MIN));
+        assert_eq!(OverflowPolicy::Wrap.sub(i32::MIN, 1), Some(i32::MAX));
+        assert_eq!(OverflowPolicy::Ignore.sub(i32::MIN, 1), None);
+        // 範囲を超えない場合はどれも同じ結果になる
+        for policy in [
+            OverflowPolicy::Saturate,
+            OverflowPolicy::Wrap,
+            OverflowPolicy::Ignore,
+        ] {
+            assert_eq!(policy.add(1, 2), Some(3));
+            assert_eq!(policy.sub(1, 2), Some(-1));
+        }
+    }
+
+    fn count_after_overflow(policy: OverflowPolicy, event: AppEvent) -> i32 {
+        let data = AppData::builder()
+            .count(i32::MAX)
+            .overflow_policy(policy)
+            .build()
+            .unwrap();
+        let mut harness = CounterHarness::new(data);
+        harness.send_app(event);
+        harness.count()
+    }
+
+    #[test]
+    fn overflow_policy_applies_to_app_events() {
+        assert_eq!(
+            count_after_overflow(OverflowPolicy::Saturate, AppEvent::Add(1)),
+            i32::MAX
+        );
+        assert_eq!(
+            count_after_overflow(OverflowPolicy::Wrap, AppEvent::Add(1)),
+            i32::MIN
+        );
+        assert_eq!(
+            count_after_overflow(OverflowPolicy::Ignore, AppEvent::Add(1)),
+            i32::MAX
+        );
+        assert_eq!(
+            count_after_overflow(OverflowPolicy::Wrap, AppEvent::Increment(1)),
+            i32::MIN
+        );
+    }
+
+    #[test]
+    fn ignored_overflow_is_not_recorded() {
+        let data = AppData::builder()
+            .count(i32::MAX)
+            .overflow_policy(OverflowPolicy::Ignore)
+            .build()
+            .unwrap();
+        let mut harness = CounterHarness::new(data);
+        let before = harness.snapshot();
+        harness.send_app(AppEvent::Add(1));
+        // countも履歴も変わらない
+        assert!(before.changed_fields(&harness.snapshot()).is_empty());
+    }
+
+    #[test]
+    fn wrap_moves_to_the_opposite_end() {
+        let data = AppData::builder()
+            .count(20)
+            .min(0)
+            .max(23)
+            .step(5)
+            .build()
+            .unwrap();
+        let mut harness = CounterHarness::with_counter(data, |counter| counter.wrap(true));
+        // 端に達するまでは飽和し，端から先に進めると反対側の端に移る
+        let mut seen = Vec::new();
+        for _ in 0..3 {
+            harness.increment();
+            seen.push(harness.count());
+        }
+        assert_eq!(seen, vec![23, 0, 5]);
+        harness.decrement();
+        harness.decrement();
+        assert_eq!(harness.count(), 23);
+    }
+
+    #[test]
+    fn without_wrap_the_ends_saturate() {
+        let data = AppData::builder().count(23).min(0).max(23).build().unwrap();
+        let mut harness = CounterHarness::new(data);
+        harness.increment();
+        assert_eq!(harness.count(), 23);
+    }
+
+    #[test]
+    fn unit_uses_the_singular_only_for_one() {
+        let data = AppData::builder().min(-5).max(5).build().unwrap();
+        let mut harness =
+            CounterHarness::with_counter(data, |counter| counter.unit("item", "items"));
+        assert_eq!(harness.display(), "0 items");
+        harness.increment();
+        assert_eq!(harness.display(), "1 item");
+        harness.increment();
+        assert_eq!(harness.display(), "2 items");
+        harness.send_app(AppEvent::SetValue(-1));
+        assert_eq!(harness.display(), "-1 items");
+    }
+
+    #[test]
+    fn format_takes_precedence_over_unit() {
+        let mut harness = CounterHarness::with_counter(AppData::default(), |counter| {
+            counter
+                .unit("item", "items")
+                .format(|value| format!("#{}", value))
+        });
+        harness.increment();
+        assert_eq!(harness.display(), "#1");
+    }
+
+    #[test]
+    fn autofocus_is_applied_only_once() {
+        let mut harness = CounterHarness::with_counter(AppData::default(), |counter| {
+            counter.autofocus(FocusTarget::IncrementButton)
+        });
+        let counter = harness.counter();
+        // フォーカスした後はNoneに戻り，値が変わって子要素が作り直されても再びフォーカスしない
+        let autofocus = harness
+            .context()
+            .with_current(counter, |cx| CounterLayoutState::autofocus.get(cx));
+        assert!(autofocus.is_none());
+    }
+
+    #[test]
+    fn setting_the_same_value_is_not_recorded() {
+        let mut harness = CounterHarness::new(AppData::default());
+        harness.send_app(AppEvent::SetValue(0));
+        harness.send_app(AppEvent::Reset);
+        assert!(harness.snapshot().history.is_empty());
+    }
+
+    #[test]
+    fn a_slider_drag_is_one_history_entry() {
+        let data = AppData::builder().min(0).max(10).build().unwrap();
+        let mut harness = CounterHarness::new(data);
+        for value in [3, 3, 5, 7] {
+            harness.send_app(AppEvent::DragValue(value));
+        }
+        // ドラッグ中も表示はcountに合わせて更新される
+        assert_eq!(harness.display(), "7");
+        assert!(harness.snapshot().history.is_empty());
+        harness.send_app(AppEvent::EndDrag);
+        assert_eq!(harness.snapshot().history, vec![0]);
+        harness.send_app(AppEvent::Undo);
+        assert_eq!(harness.count(), 0);
+    }
+
+    #[test]
+    fn another_event_ends_the_drag() {
+        let data = AppData::builder().min(0).max(10).build().unwrap();
+        let mut harness = CounterHarness::new(data);
+        harness.send_app(AppEvent::DragValue(4));
+        harness.increment();
+        assert_eq!(harness.count(), 5);
+        assert_eq!(harness.snapshot().history, vec![0, 4]);
+    }
+
+    #[test]
+    fn on_error_receives_the_reason() {
+        let errors = Rc::new(RefCell::new(Vec::new()));
+        let captured = errors.clone();
+        let data = AppData::builder().min(0).max(10).build().unwrap();
+        let mut harness = CounterHarness::with_counter(data, move |counter| {
+            counter.on_error(move |_, message| captured.borrow_mut().push(message.to_owned()))
+        });
+        for text in ["", "abc", "42", "7"] {
+            harness.send_counter(CounterEvent::SubmitEntry(text.to_owned(), true));
+        }
+        assert_eq!(
+            *errors.borrow(),
+            vec![
+                "Enter a number",
+                "\"abc\" is not a number",
+                "Value must be between 0 and 10",
+            ]
+        );
+        // 受け付けられた値はon_errorを呼び出さずに設定される
+        assert_eq!(harness.count(), 7);
+    }
+
+    #[test]
+    fn reached_lens_turns_true_at_the_target() {
+        let data = AppData::builder().count(3).max(10).build().unwrap();
+        let mut harness = CounterHarness::new(data);
+        let reached = Counter::<i32>::reached_lens(AppData::count, 5);
+        let mut seen = Vec::new();
+        for _ in 0..3 {
+            harness.increment();
+            seen.push(reached.get(harness.context()));
+        }
+        // 4では届かず，5とそれを超えた6ではtrueになる
+        assert_eq!(seen, vec![false, true, true]);
+    }
+
+    #[test]
+    fn pending_adds_are_applied_before_other_events() {
+        let mut harness = CounterHarness::new(AppData::default());
+        // 同じflushの間にAddとResetが届いても，届いた順に処理される
+        harness.context().emit_to(Entity::root(), AppEvent::Add(1));
+        harness.context().emit_to(Entity::root(), AppEvent::Reset);
+        harness.flush();
+        assert_eq!(harness.count(), 0);
+        assert_eq!(harness.snapshot().history, vec![0, 1]);
+        // Undoで取り消されるのは直前のResetである
+        harness.send_app(AppEvent::Undo);
+        assert_eq!(harness.count(), 1);
+    }
+
+    #[test]
+    fn many_adds_in_one_flush_are_one_change() {
+        let mut harness = CounterHarness::new(AppData::default());
+        for _ in 0..1000 {
+            harness.context().emit_to(Entity::root(), AppEvent::Add(1));
+        }
+        harness.flush();
+        let snapshot = harness.snapshot();
+        assert_eq!(snapshot.count, 1000);
+        assert_eq!(snapshot.history, vec![0]);
+        assert!(snapshot.pending.is_empty());
+        assert_eq!(harness.display(), "1000");
+    }
+
+    #[test]
+    fn set_range_updates_a_bound_counter() {
+        let data = AppData::builder().count(8).min(0).max(10).build().unwrap();
+        let mut harness = CounterHarness::with_view(data, |cx, clock| {
+            Counter::new(cx, AppData::count)
+                .clock(clock)
+                .range_lens(AppData::min, AppData::max)
+                .on_increment(|ex, amount| ex.emit(AppEvent::Add(amount)))
+                .entity()
+        });
+        harness.send_app(AppEvent::SetRange { min: 0, max: 5 });
+        // モデルのcountが範囲内に収められ，Counterも新しい上限に達した状態になる
+        assert_eq!(harness.count(), 5);
+        let state = harness.state();
+        assert_eq!((state.min, state.max), (Some(0), Some(5)));
+        harness.increment();
+        assert_eq!(harness.count(), 5);
+
+        // 範囲を広げると再び増やせる
+        harness.send_app(AppEvent::SetRange { min: 0, max: 20 });
+        harness.increment();
+        assert_eq!(harness.count(), 6);
+    }
+
+    #[test]
+    fn show_rate_reports_recent_changes() {
+        let data = AppData::builder().max(10).build().unwrap();
+        let mut harness =
+            CounterHarness::with_counter(data, |counter| counter.show_rate(Duration::from_secs(5)));
+        harness.increment();
+        harness.increment();
+        assert_eq!(harness.rate(), "+2 in the last 5s");
+        harness.advance(Duration::from_secs(6));
+        harness.send_counter(CounterEvent::RateTick);
+        assert_eq!(harness.rate(), "0 in the last 5s");
+    }
+
+    // 別のモデルからCounter::emit_increment・emit_decrementで操作する例
+    struct Nudger {
+        counter: Entity,
+    }
+
+    enum NudgeEvent {
+        Up,
+        Down,
+    }
+
+    impl Model for Nudger {
+        fn event(&mut self, cx: &mut EventContext, event: &mut Event) {
+            event.map(|nudge_event, _| match nudge_event {
+                NudgeEvent::Up => Counter::<i32>::emit_increment(cx, self.counter),
+                NudgeEvent::Down => Counter::<i32>::emit_decrement(cx, self.counter),
+            });
+        }
+    }
+
+    #[test]
+    fn emit_helpers_step_the_counter_from_outside() {
+        let data = AppData::builder().min(0).max(1).build().unwrap();
+        let mut harness = CounterHarness::with_view(data, |cx, clock| {
+            let counter = Counter::new(cx, AppData::count)
+                .clock(clock)
+                .range(0, 1)
+                .on_increment(|ex, amount| ex.emit(AppEvent::Add(amount)))
+                .on_decrement(|ex, amount| ex.emit(AppEvent::Add(-amount)))
+                .entity();
+            Nudger { counter }.build(cx);
+            counter
+        });
+        // ボタンを押したときと同じように範囲の判定を通るため，上限を超えない
+        for _ in 0..2 {
+            harness.context().emit_to(Entity::root(), NudgeEvent::Up);
+            harness.flush();
+        }
+        assert_eq!(harness.count(), 1);
+        harness.context().emit_to(Entity::root(), NudgeEvent::Down);
+        harness.flush();
+        assert_eq!(harness.count(), 0);
+    }
+
+    #[test]
+    fn set_all_replaces_every_count() {
+        let mut harness = CounterHarness::new(AppData::default());
+        harness.send_app(AppEvent::SetAll(vec![2, 1, 0]));
+        assert_eq!(harness.snapshot().counts, vec![2, 1, 0]);
+        // 区分ごとの範囲に収められる
+        harness.send_app(AppEvent::SetAll(vec![-1, 4, 99]));
+        assert_eq!(harness.snapshot().counts, vec![0, 4, 9]);
+        assert_eq!(AppData::count_at(2).get(harness.context()), 9);
+    }
+
+    #[test]
+    fn set_at_clamps_to_the_guest_range() {
+        let (min, max) = GUEST_RANGE;
+        let mut harness = CounterHarness::new(AppData::default());
+        harness.send_app(AppEvent::SetAt(0, max + 1));
+        harness.send_app(AppEvent::SetAt(2, min - 1));
+        harness.send_app(AppEvent::SetAt(1, 4));
+        assert_eq!(harness.snapshot().counts, vec![max, 4, min]);
+    }
+
+    #[test]
+    fn set_all_ignores_a_wrong_number_of_values() {
+        let mut harness = CounterHarness::new(AppData::default());
+        harness.send_app(AppEvent::SetAll(vec![1, 1, 1]));
+        harness.send_app(AppEvent::SetAll(vec![5, 5]));
+        harness.send_app(AppEvent::SetAll(vec![5, 5, 5, 5]));
+        assert_eq!(harness.snapshot().counts, vec![1, 1, 1]);
+    }
+
+    // Counterの親として，届いたイベントを数えるビュー
+    struct Probe {
+        counter_events: Rc<Cell<usize>>,
+        pings: Rc<Cell<usize>>,
+    }
+
+    struct Ping;
+
+    impl View for Probe {
+        fn event(&mut self, _cx: &mut EventContext, event: &mut Event) {
+            event.map(|_: &CounterEvent<i32>, _| {
+                self.counter_events.set(self.counter_events.get() + 1)
+            });
+            event.map(|_: &Ping, _| self.pings.set(self.pings.get() + 1));
+        }
+    }
+
+    #[test]
+    fn counter_events_do_not_reach_ancestors() {
+        let counter_events = Rc::new(Cell::new(0));
+        let pings = Rc::new(Cell::new(0));
+        let probe = Probe {
+            counter_events: counter_events.clone(),
+            pings: pings.clone(),
+        };
+        let mut harness = CounterHarness::with_view(AppData::default(), |cx, clock| {
+            let mut counter = Entity::null();
+            probe.build(cx, |cx| {
+                counter = Counter::new(cx, AppData::count)
+                    .clock(clock)
+                    .on_increment(|ex, amount| ex.emit(AppEvent::Add(amount)))
+                    .entity();
+            });
+            counter
+        });
+        let counter = harness.counter();
+        // 子要素から送られたときと同じように，Counterから親に向かって伝搬させる
+        for message in [CounterEvent::<i32>::Increment, CounterEvent::Decrement] {
+            harness.context().emit_custom(
+                Event::new(message)
+                    .target(counter)
+                    .origin(counter)
+                    .propagate(Propagation::Up),
+            );
+        }
+        harness.context().emit_custom(
+            Event::new(Ping)
+                .target(counter)
+                .origin(counter)
+                .propagate(Propagation::Up),
+        );
+        harness.flush();
+        // Counterで消費されたイベントは親に届かないが，それ以外のイベントは届く
+        assert_eq!(counter_events.get(), 0);
+        assert_eq!(pings.get(), 1);
+        assert_eq!(harness.count(), 1);
+    }
+
+    #[test]
+    fn typing_digits_sets_the_value_on_enter() {
+        let mut harness = CounterHarness::new(AppData::default());
+        for code in [Code::Digit1, Code::Digit2, Code::Digit3] {
+            harness.key_down(code);
+        }
+        // 確定するまではLabelに入力中の値を表示し，モデルは変えない
+        assert_eq!(harness.display(), "123");
+        assert_eq!(harness.count(), 0);
+        harness.key_down(Code::Enter);
+        assert_eq!(harness.count(), 123);
+        assert_eq!(harness.display(), "123");
+    }
+
+    #[test]
+    fn escape_discards_the_typed_digits() {
+        let mut harness = CounterHarness::new(AppData::default());
+        harness.key_down(Code::Digit4);
+        harness.key_down(Code::Digit2);
+        harness.key_down(Code::Escape);
+        assert_eq!(harness.count(), 0);
+        assert_eq!(harness.display(), "0");
+    }
+
+    #[test]
+    fn typed_digits_commit_after_a_pause() {
+        let mut harness = CounterHarness::new(AppData::default());
+        harness.key_down(Code::Digit7);
+        harness.send_counter(CounterEvent::TypeAheadElapsed);
+        assert_eq!(harness.count(), 7);
+    }
+
+    #[test]
+    fn parse_value_prefers_plain_numbers() {
+        // 数値として解析できる入力は式として扱わないため，小数も受け付けられる
+        assert_eq!(parse_value::<f64>("3.5", true), Ok(3.5));
+        assert_eq!(parse_value::<f64>("3.5", false), Ok(3.5));
+        assert_eq!(parse_value::<i32>("-12", true), Ok(-12));
+        // 解析できない場合だけ整数の計算式として計算する
+        assert_eq!(parse_value::<i32>("3+4", true), Ok(7));
+        assert_eq!(parse_value::<f64>("(2+3)*10", true), Ok(50.0));
+        assert_eq!(
+            parse_value::<f64>("1.5*2", true),
+            Err(String::from("Unexpected \".\""))
+        );
+    }
+
+    #[test]
+    fn parse_value_explains_rejected_input() {
+        assert_eq!(
+            parse_value::<i32>("  ", true),
+            Err(String::from("Enter a number"))
+        );
+        assert_eq!(
+            parse_value::<i32>("3+4", false),
+            Err(String::from("\"3+4\" is not a number"))
+        );
+        assert_eq!(
+            parse_value::<u8>("1000", true),
+            Err(String::from("1000 is out of range"))
+        );
+    }
+
+    #[test]
+    fn expressions_are_accepted_in_text_entry() {
+        let mut harness = CounterHarness::with_counter(AppData::default(), |counter| {
+            counter.allow_expressions(true)
+        });
+        harness.send_counter(CounterEvent::SubmitEntry(String::from("(2+3)*4"), true));
+        assert_eq!(harness.count(), 20);
+    }
+
+    #[test]
+    fn preview_on_hold_commits_on_release() {
+        let data = AppData::builder().max(10).build().unwrap();
+        let mut harness =
+            CounterHarness::with_counter(data, |counter| counter.preview_on_hold(true));
+        harness.send_counter(CounterEvent::StartRepeat(StepDirection::Increment));
+        // 押している間は変更後の値を表示するだけで，モデルは変えない
+        assert_eq!(harness.display(), "1");
+        assert_eq!(harness.count(), 0);
+        // on_pressとon_mouse_upの両方からStopRepeatが届いても1回だけ変化する
+        harness.send_counter(CounterEvent::StopRepeat);
+        harness.send_counter(CounterEvent::StopRepeat);
+        assert_eq!(harness.count(), 1);
+        assert_eq!(harness.display(), "1");
+    }
+
+    #[test]
+    fn preview_on_hold_is_cancelled_when_the_pointer_leaves() {
+        let data = AppData::builder().max(10).build().unwrap();
+        let mut harness =
+            CounterHarness::with_counter(data, |counter| counter.preview_on_hold(true));
+        harness.send_counter(CounterEvent::StartRepeat(StepDirection::Increment));
+        harness.send_counter(CounterEvent::CancelPress);
+        harness.send_counter(CounterEvent::StopRepeat);
+        assert_eq!(harness.count(), 0);
+        assert_eq!(harness.display(), "0");
+    }
+
+    #[test]
+    fn reset_is_announced_once_the_value_arrives() {
+        let data = AppData::builder().max(10).build().unwrap();
+        let mut harness = CounterHarness::new(data);
+        harness.increment();
+        harness.send_counter(CounterEvent::Reset);
+        assert_eq!(harness.announcement(), "Reset to 0");
+        // その後の変化では読み上げを消す
+        harness.increment();
+        assert_eq!(harness.announcement(), "");
+    }
+
+    #[test]
+    fn a_rejected_default_value_is_not_announced() {
+        let data = AppData::builder().min(0).max(10).build().unwrap();
+        let mut harness = CounterHarness::with_counter(data, |counter| counter.default_value(50));
+        harness.increment();
+        harness.send_counter(CounterEvent::Reset);
+        assert_eq!(harness.count(), 1);
+        // 範囲外のため設定されず，次の変化をリセットとして読み上げない
+        harness.increment();
+        assert_eq!(harness.count(), 2);
+        assert_eq!(harness.announcement(), "");
+    }
+
+    #[test]
+    fn reset_without_on_reset_is_not_announced() {
+        let mut harness = CounterHarness::with_view(AppData::default(), |cx, clock| {
+            Counter::new(cx, AppData::count)
+                .clock(clock)
+                .on_increment(|ex, amount| ex.emit(AppEvent::Add(amount)))
+                .entity()
+        });
+        harness.increment();
+        harness.send_counter(CounterEvent::Reset);
+        harness.increment();
+        assert_eq!(harness.count(), 2);
+        assert_eq!(harness.announcement(), "");
+    }
+
+    #[test]
+    fn an_ignored_reset_stops_waiting_on_the_next_action() {
+        // on_resetは設定されているが，モデルが何もしない場合
+        let mut harness = CounterHarness::with_view(AppData::default(), |cx, clock| {
+            Counter::new(cx, AppData::count)
+                .clock(clock)
+                .on_increment(|ex, amount| ex.emit(AppEvent::Add(amount)))
+                .on_reset(|_| {})
+                .entity()
+        });
+        harness.increment();
+        harness.send_counter(CounterEvent::Reset);
+        harness.increment();
+        assert_eq!(harness.count(), 2);
+        assert_eq!(harness.announcement(), "");
+    }
+
+    #[test]
+    fn layout_orders_the_horizontal_parts() {
+        use CounterPart::*;
+        // 既定では値を減少・増加ボタンで挟む
+        assert_eq!(
+            horizontal_parts(
+                LayoutDirection::LeftToRight,
+                true,
+                CounterLayout::MinusValuePlus
+            ),
+            vec![Decrement, Value, Increment]
+        );
+        assert_eq!(
+            horizontal_parts(
+                LayoutDirection::LeftToRight,
+                false,
+                CounterLayout::ValueThenButtons
+            ),
+            vec![Value, Decrement, Increment, Entry, Reset]
+        );
+        // 右から左では全体を反転する
+        assert_eq!(
+            horizontal_parts(
+                LayoutDirection::RightToLeft,
+                true,
+                CounterLayout::ButtonsThenValue
+            ),
+            vec![Value, Increment, Decrement]
+        );
+    }
+
+    #[test]
+    fn layout_defaults_to_minus_value_plus() {
+        let mut harness = CounterHarness::new(AppData::default());
+        let counter = harness.counter();
+        let layout = harness
+            .context()
+            .with_current(counter, |cx| CounterLayoutState::layout.get(cx));
+        assert_eq!(layout, CounterLayout::MinusValuePlus);
+
+        let mut harness = CounterHarness::with_counter(AppData::default(), |counter| {
+            counter.layout(CounterLayout::ValueThenButtons)
+        });
+        let counter = harness.counter();
+        let layout = harness
+            .context()
+            .with_current(counter, |cx| CounterLayoutState::layout.get(cx));
+        assert_eq!(layout, CounterLayout::ValueThenButtons);
+    }
+
+    #[test]
+    fn step_sets_the_amount_of_each_press() {
+        // with_counterはAppData::stepをCounterのstepに渡す
+        let data = AppData::builder().step(5).build().unwrap();
+        let mut harness = CounterHarness::new(data);
+        harness.increment();
+        harness.increment();
+        assert_eq!(harness.count(), 10);
+        assert_eq!(harness.display(), "10");
+    }
+
+    #[test]
+    fn counter_works_with_f64() {
+        let mut harness = CounterHarness::with_view(AppData::default(), |cx, clock| {
+            Counter::<f64>::new(cx, AppData::weight_lens())
+                .clock(clock)
+                .step(0.5)
+                .range(0.0, 1.0)
+                .on_increment(|ex, amount| ex.emit(AppEvent::AdjustWeight(amount)))
+                .on_decrement(|ex, amount| ex.emit(AppEvent::AdjustWeight(-amount)))
+                .entity()
+        });
+        let counter = harness.counter();
+        for _ in 0..3 {
+            harness
+                .context()
+                .emit_to(counter, CounterEvent::<f64>::Increment);
+            harness.flush();
+        }
+        // 上限の1.0で止まる
+        assert_eq!(AppData::weight_lens().get(harness.context()), 1.0);
+        harness
+            .context()
+            .emit_to(counter, CounterEvent::<f64>::Decrement);
+        harness.flush();
+        assert_eq!(AppData::weight_lens().get(harness.context()), 0.5);
+    }
+
+    #[test]
+    fn counter_works_with_u32() {
+        let mut harness = CounterHarness::with_view(AppData::default(), |cx, clock| {
+            Counter::<u32>::new(cx, AppData::rooms)
+                .clock(clock)
+                .range(ROOMS_RANGE.0, ROOMS_RANGE.1)
+                .on_increment(|ex, amount| ex.emit(AppEvent::AddRooms(amount)))
+                .on_decrement(|ex, amount| ex.emit(AppEvent::RemoveRooms(amount)))
+                .entity()
+        });
+        let counter = harness.counter();
+        harness
+            .context()
+            .emit_to(counter, CounterEvent::<u32>::Increment);
+        harness.flush();
+        assert_eq!(AppData::rooms.get(harness.context()), 2);
+        // 下限の1より下には減らない．u32が0を下回って溢れることもない
+        for _ in 0..3 {
+            harness
+                .context()
+                .emit_to(counter, CounterEvent::<u32>::Decrement);
+            harness.flush();
+        }
+        assert_eq!(AppData::rooms.get(harness.context()), 1);
+    }
+
+    #[test]
+    fn undo_and_redo_stay_within_the_current_range() {
+        let data = AppData::builder().min(0).max(10).build().unwrap();
+        let mut harness = CounterHarness::new(data);
+        for _ in 0..3 {
+            harness.increment();
+        }
+        harness.send_app(AppEvent::SetRange { min: 2, max: 10 });
+        harness.send_app(AppEvent::Undo);
+        assert_eq!(harness.count(), 2);
+        // 履歴の1は新しい下限の2に収める
+        harness.send_app(AppEvent::Undo);
+        assert_eq!(harness.count(), 2);
+
+        harness.send_app(AppEvent::SetRange { min: 0, max: 2 });
+        harness.send_app(AppEvent::Redo);
+        harness.send_app(AppEvent::Redo);
+        // やり直す値の3も新しい上限の2に収める
+        assert_eq!(harness.count(), 2);
+    }
+
+    #[test]
+    fn only_changes_to_saved_values_are_written() {
+        let path = temp_path("saved-values");
+        let _ = fs::remove_file(&path);
+        let data = AppData {
+            save_path: Some(path.clone()),
+            ..AppData::default()
+        };
+        let mut harness = CounterHarness::new(data);
+        // テーマ・並び・表示用ウィンドウ・未保存の印はファイルに含まれない
+        for event in [
+            AppEvent::ToggleTheme,
+            AppEvent::ToggleDirection,
+            AppEvent::OpenDisplayWindow,
+            AppEvent::CloseDisplayWindow,
+            AppEvent::MarkClean,
+            AppEvent::EndDrag,
+            AppEvent::ApplyPending,
+        ] {
+            harness.send_app(event);
+        }
+        assert!(!path.exists());
+
+        harness.increment();
+        assert_eq!(AppData::load(&path).count, 1);
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn stepping_an_indexed_counter_stays_within_the_guest_range() {
+        let (min, max) = GUEST_RANGE;
+        let mut harness = CounterHarness::new(AppData::default());
+        harness.send_app(AppEvent::IncrementAt(0, max + 5));
+        harness.send_app(AppEvent::DecrementAt(1, 3));
+        harness.send_app(AppEvent::Step(guest_id(2), StepDirection::Increment, 100));
+        assert_eq!(harness.snapshot().counts, vec![max, min, max]);
+    }
+
+    #[test]
+    fn locking_through_the_lens_ignores_increment() {
+        let mut harness = CounterHarness::with_counter(AppData::default(), |counter| {
+            counter.disabled_when(AppData::locked)
+        });
+        harness.increment();
+        assert_eq!(harness.count(), 1);
+        // Lensの値がtrueになった時点から受け付けなくなる
+        harness.send_app(AppEvent::ToggleLocked);
+        assert!(harness.snapshot().locked);
+        harness.increment();
+        assert_eq!(harness.count(), 1);
+    }
+
+    #[test]
+    fn value_reads_the_cached_count() {
+        let built = Rc::new(Cell::new(None));
+        let mut harness = CounterHarness::with_counter(AppData::default(), |counter| {
+            let built = built.clone();
+            counter.value(move |value| built.set(Some(value)))
+        });
+        // 作成した時点ではLensの初期値が渡される
+        assert_eq!(built.get(), Some(0));
+        harness.increment();
+        harness.increment();
+        // valueとstateはどちらもLensから通知された値のキャッシュを読み出す
+        assert_eq!(harness.state().value, 2);
+        assert_eq!(harness.count(), 2);
+    }
+
+    #[test]
+    fn count_class_replaces_the_default_class() {
+        let mut harness = CounterHarness::new(AppData::default());
+        assert!(harness.find("count").is_some());
+
+        let mut harness =
+            CounterHarness::with_counter(AppData::default(), |counter| counter.count_class("goal"));
+        let label = harness.find("goal").expect("the value label has the class");
+        assert!(harness.has_class(label, "value"));
+        assert!(harness.find("count").is_none());
+    }
+
+    #[test]
+    fn buttons_and_label_carry_accessibility_metadata() {
+        let mut harness = CounterHarness::with_counter(AppData::default(), |counter| {
+            counter.accessibility_label("Guests")
+        });
+        let counter = harness.counter();
+        assert_eq!(harness.role(counter), Some(Role::Group));
+        assert_eq!(harness.name(counter).as_deref(), Some("Guests"));
+
+        let increment = harness.find("inc").unwrap();
+        assert_eq!(harness.role(increment), Some(Role::Button));
+        assert_eq!(harness.name(increment).as_deref(), Some("Increment"));
+        let decrement = harness.find("dec").unwrap();
+        assert_eq!(harness.role(decrement), Some(Role::Button));
+        assert_eq!(harness.name(decrement).as_deref(), Some("Decrement"));
+
+        // 値のLabelは変化したときに読み上げられるライブリージョンとする
+        let label = harness.find("count").unwrap();
+        assert_eq!(harness.live(label), Some(Live::Polite));
+    }
+
+    #[test]
+    fn one_press_at_step_five_is_one_add() {
+        let events = Rc::new(RefCell::new(Vec::new()));
+        let data = AppData::builder()
+            .step(5)
+            .on_event({
+                let events = events.clone();
+                move |event| events.borrow_mut().push(format!("{:?}", event))
+            })
+            .build()
+            .unwrap();
+        let mut harness = CounterHarness::new(data);
+        events.borrow_mut().clear();
+        harness.increment();
+        // 5回のAdd(1)ではなく，変化量をまとめた1つのAdd(5)が届く
+        let events = events.borrow();
+        let adds: Vec<_> = events
+            .iter()
+            .filter(|event| event.starts_with("Add("))
+            .collect();
+        assert_eq!(adds, ["Add(5)"]);
+        assert_eq!(harness.count(), 5);
+    }
+
+    #[test]
+    fn scrubbing_steps_once_per_sensitivity() {
+        let mut harness = CounterHarness::with_counter(AppData::default(), |counter| {
+            counter.scrub_sensitivity(20.0)
+        });
+        harness.send_counter(CounterEvent::ScrubStart(100.0));
+        // 右へ65px動かすと3step増え，端数の5pxは次の移動に持ち越す
+        harness.send_counter(CounterEvent::ScrubMove(165.0));
+        assert_eq!(harness.count(), 3);
+        // 左へ動かすと減る
+        harness.send_counter(CounterEvent::ScrubMove(120.0));
+        harness.send_counter(CounterEvent::ScrubEnd);
+        assert_eq!(harness.count(), 1);
+    }
+
+    #[test]
+    fn a_click_without_dragging_does_not_step() {
+        let mut harness = CounterHarness::new(AppData::default());
+        harness.send_counter(CounterEvent::ScrubStart(100.0));
+        harness.send_counter(CounterEvent::ScrubMove(105.0));
+        harness.send_counter(CounterEvent::ScrubEnd);
+        assert_eq!(harness.count(), 0);
+    }
+
+    #[test]
+    fn snapped_values_stay_within_a_range_narrower_than_the_step() {
+        let data = AppData::builder().min(0).max(3).step(5).build().unwrap();
+        let mut harness = CounterHarness::with_counter(data, |counter| counter.snap(true));
+        harness.send_counter(CounterEvent::SubmitEntry(String::from("2"), true));
+        assert_eq!(harness.count(), 0);
+        // 10に丸めた後，1step内側の5も上限を超えるため上限の3にする
+        harness.send_counter(CounterEvent::SubmitEntry(String::from("8"), true));
+        assert_eq!(harness.count(), 3);
+    }
+
+    #[test]
+    fn right_to_left_puts_increment_on_the_left() {
+        let classes = ["inc", "count", "dec"];
+        let mut harness = CounterHarness::with_counter(AppData::default(), |counter| {
+            counter.direction(LayoutDirection::RightToLeft)
+        });
+        let row = harness.find("row").unwrap();
+        assert!(harness.has_class(row, "rtl"));
+        assert_eq!(harness.class_order(&classes), ["inc", "count", "dec"]);
+        // 表示の順が変わっても，"inc"のクラスは増加ボタンに付いたままとなる
+        let increment = harness.find("inc").unwrap();
+        assert_eq!(harness.name(increment).as_deref(), Some("Increment"));
+
+        let mut harness = CounterHarness::new(AppData::default());
+        assert_eq!(harness.class_order(&classes), ["dec", "count", "inc"]);
+    }
+
+    #[test]
+    fn value_ratio_maps_the_range_to_zero_and_one() {
+        assert_eq!(value_ratio(-10, -10, 30), 0.0);
+        assert_eq!(value_ratio(10, -10, 30), 0.5);
+        assert_eq!(value_ratio(30, -10, 30), 1.0);
+        // 範囲外の値は0.0と1.0に丸める
+        assert_eq!(value_ratio(-20, -10, 30), 0.0);
+        assert_eq!(value_ratio(40, -10, 30), 1.0);
+        assert_eq!(value_ratio(2.5, 0.0, 10.0), 0.25);
+    }
+
+    #[test]
+    fn ratio_lens_follows_the_count() {
+        let data = AppData::builder().count(3).build().unwrap();
+        let mut harness = CounterHarness::new(data);
+        let ratio = Counter::<i32>::ratio_lens(AppData::count, 0, 4);
+        assert_eq!(ratio.get(harness.context()), 0.75);
+        harness.increment();
+        assert_eq!(ratio.get(harness.context()), 1.0);
+    }
+
+    #[test]
+    fn wheel_steps_once_per_line() {
+        let mut harness =
+            CounterHarness::with_counter(AppData::default(), |counter| counter.wheel(true));
+        harness.send_window(WindowEvent::MouseScroll(0.0, 2.0));
+        assert_eq!(harness.count(), 2);
+        // トラックパッドの小さな移動量は1行分に達するまで貯める
+        harness.send_window(WindowEvent::MouseScroll(0.0, -0.6));
+        assert_eq!(harness.count(), 2);
+        harness.send_window(WindowEvent::MouseScroll(0.0, -0.6));
+        assert_eq!(harness.count(), 1);
+    }
+
+    #[test]
+    fn wheel_is_ignored_unless_enabled() {
+        let mut harness = CounterHarness::new(AppData::default());
+        harness.send_window(WindowEvent::MouseScroll(0.0, 3.0));
+        assert_eq!(harness.count(), 0);
+    }
+
+    // 小数の値をそのままf64で持つモデル
+    // AppDataのweightは小数点以下1桁に丸めて保持するため，それより細かい値や丸めていない値を確かめるときに使う
+    #[derive(Lens)]
+    struct Price {
+        value: f64,
+    }
+
+    enum PriceEvent {
+        Add(f64),
+    }
+
+    impl Model for Price {
+        fn event(&mut self, _cx: &mut EventContext, event: &mut Event) {
+            event.map(|PriceEvent::Add(amount), _| self.value += amount);
+        }
+    }
+
+    #[test]
+    fn precision_rounds_the_displayed_decimals() {
+        let mut harness = CounterHarness::with_view(AppData::default(), |cx, clock| {
+            Price { value: 3.14159 }.build(cx);
+            Counter::new(cx, Price::value)
+                .clock(clock)
+                .precision(2)
+                .entity()
+        });
+        assert_eq!(harness.display(), "3.14");
+    }
+
+    #[test]
+    fn on_event_sees_every_event_in_order() {
+        let events = Rc::new(RefCell::new(Vec::new()));
+        let data = AppData::builder()
+            .on_event({
+                let events = events.clone();
+                move |event| events.borrow_mut().push(format!("{:?}", event))
+            })
+            .build()
+            .unwrap();
+        let mut harness = CounterHarness::new(data);
+        events.borrow_mut().clear();
+        harness.increment();
+        harness.send_app(AppEvent::Undo);
+        harness.send_app(AppEvent::ToggleTheme);
+        assert_eq!(
+            *events.borrow(),
+            ["Add(1)", "ApplyPending", "Undo", "ToggleTheme"]
+        );
+        // フックはイベントを処理する前に呼び出されるだけで，処理の結果は変わらない
+        assert_eq!(harness.count(), 0);
+    }
+
+    #[test]
+    fn animate_on_change_pulses_until_the_timer_ends() {
+        let mut harness = CounterHarness::with_counter(AppData::default(), |counter| {
+            counter.animate_on_change(true)
+        });
+        let counter = harness.counter();
+        assert!(!harness.has_class(counter, "pulse"));
+        harness.increment();
+        assert!(harness.has_class(counter, "pulse"));
+        // 短い時間の後にタイマーからPulseEndが届くと元に戻る
+        harness.send_counter(CounterEvent::PulseEnd);
+        assert!(!harness.has_class(counter, "pulse"));
+
+        let mut harness = CounterHarness::new(AppData::default());
+        let counter = harness.counter();
+        harness.increment();
+        assert!(!harness.has_class(counter, "pulse"));
+    }
+
+    #[test]
+    fn pill_applies_its_class_and_the_compact_layout() {
+        let mut harness = CounterHarness::with_view(AppData::default(), |cx, clock| {
+            Counter::pill(cx, AppData::count).clock(clock).entity()
+        });
+        let counter = harness.counter();
+        assert!(harness.has_class(counter, "pill"));
+        assert!(harness.read(CounterLayoutState::compact));
+        // 小さな並びにはテキスト入力とリセットボタンを含めない
+        assert!(harness.find("entry").is_none());
+        assert!(harness.find("reset").is_none());
+    }
+
+    #[test]
+    fn step_lens_uses_the_step_at_press_time() {
+        let mut harness = CounterHarness::with_counter(AppData::default(), |counter| {
+            counter.step_lens(AppData::step)
+        });
+        harness.increment();
+        assert_eq!(harness.count(), 1);
+        // 他のビューからstepを変更すると，次の押下から新しいstepで変化する
+        harness.send_app(AppEvent::AddStep(2));
+        harness.increment();
+        assert_eq!(harness.count(), 4);
+        harness.decrement();
+        assert_eq!(harness.count(), 1);
+    }
+
+    #[test]
+    fn placeholder_is_shown_for_the_sentinel() {
+        let data = AppData::builder().count(-1).build().unwrap();
+        let mut harness = CounterHarness::with_counter(data, |counter| {
+            counter.placeholder(-1, "\u{2014}").placeholder_base(1)
+        });
+        assert_eq!(harness.display(), "\u{2014}");
+        let counter = harness.counter();
+        assert!(harness.has_class(counter, "placeholder"));
+        // 未設定の状態から増加させると，sentinelの次の0ではなくplaceholder_baseに移る
+        harness.increment();
+        assert_eq!(harness.count(), 1);
+        assert_eq!(harness.display(), "1");
+        assert!(!harness.has_class(counter, "placeholder"));
+    }
+
+    #[test]
+    fn context_menu_reset_resets_the_count() {
+        let data = AppData::builder().count(5).build().unwrap();
+        let mut harness = CounterHarness::with_counter(data, |counter| counter.context_menu(true));
+        harness.send_window(WindowEvent::MouseDown(MouseButton::Right));
+        assert!(harness.read(CounterLayoutState::menu_open));
+        let names: Vec<_> = harness
+            .find_all("menu-item")
+            .into_iter()
+            .map(|item| harness.name(item).unwrap_or_default())
+            .collect();
+        assert_eq!(names, ["Reset", "Set to max", "Set to min"]);
+
+        // "Reset"の項目を選んだときと同じイベントを送る
+        harness.send_counter(CounterEvent::Reset);
+        harness.send_counter(CounterEvent::CloseMenu);
+        assert_eq!(harness.count(), 0);
+        assert!(harness.find("context-menu").is_none());
+    }
+
+    #[test]
+    fn thresholds_switch_classes_at_the_boundaries() {
+        let data = AppData::builder().count(9).build().unwrap();
+        let mut harness = CounterHarness::with_counter(data, |counter| {
+            counter.thresholds(vec![(0, "ok"), (10, "warn"), (20, "alert")])
+        });
+        let counter = harness.counter();
+        let active = |harness: &mut CounterHarness| {
+            ["ok", "warn", "alert"]
+                .into_iter()
+                .filter(|class| harness.has_class(counter, class))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(active(&mut harness), ["ok"]);
+        // 下限と等しい値からその範囲のクラスになり，同時に付与されるのは1つだけとする
+        harness.increment();
+        assert_eq!(active(&mut harness), ["warn"]);
+        harness.send_app(AppEvent::SetValue(19));
+        assert_eq!(active(&mut harness), ["warn"]);
+        harness.increment();
+        assert_eq!(active(&mut harness), ["alert"]);
+        // どの下限も下回る場合はクラスを付与しない
+        harness.send_app(AppEvent::SetValue(-1));
+        assert!(active(&mut harness).is_empty());
+    }
+
+    #[test]
+    fn ctrl_zero_resets_the_count() {
+        let data = AppData::builder().count(5).build().unwrap();
+        let mut harness = CounterHarness::new(data);
+        // Ctrlを押していない0は数字の入力として扱い，リセットしない
+        harness.key_down(Code::Digit0);
+        harness.key_down(Code::Escape);
+        assert_eq!(harness.count(), 5);
+        harness.set_modifiers(Modifiers::CTRL);
+        harness.key_down(Code::Digit0);
+        assert_eq!(harness.count(), 0);
+    }
+
+    #[test]
+    fn reset_shortcut_replaces_ctrl_zero() {
+        let data = AppData::builder().count(5).build().unwrap();
+        let mut harness = CounterHarness::with_counter(data, |counter| {
+            counter.reset_shortcut(KeyChord::new(Modifiers::ALT, Code::KeyR))
+        });
+        harness.set_modifiers(Modifiers::CTRL);
+        harness.key_down(Code::Digit0);
+        assert_eq!(harness.count(), 5);
+        harness.set_modifiers(Modifiers::ALT);
+        harness.key_down(Code::KeyR);
+        assert_eq!(harness.count(), 0);
+    }
+
+    #[test]
+    fn tooltips_are_registered_for_each_button() {
+        let mut harness = CounterHarness::new(AppData::default());
+        assert_eq!(harness.read(CounterText::increment_tooltip), "");
+        assert_eq!(harness.read(CounterText::decrement_tooltip), "");
+
+        let mut harness = CounterHarness::with_counter(AppData::default(), |counter| {
+            counter.tooltips("Add one (\u{2191})", "Remove one (\u{2193})")
+        });
+        assert_eq!(
+            harness.read(CounterText::increment_tooltip),
+            "Add one (\u{2191})"
+        );
+        assert_eq!(
+            harness.read(CounterText::decrement_tooltip),
+            "Remove one (\u{2193})"
+        );
+        // ツールチップを設定した後も，ボタンは作り直されて1つずつ残る
+        assert_eq!(harness.find_all("inc").len(), 1);
+        assert_eq!(harness.find_all("dec").len(), 1);
+    }
+
+    #[test]
+    fn precision_keeps_the_value_as_scaled_units() {
+        let presses = Rc::new(Cell::new(0));
+        let mut harness = CounterHarness::with_view(AppData::default(), |cx, clock| {
+            Price { value: 0.0 }.build(cx);
+            let presses = presses.clone();
+            Counter::new(cx, Price::value)
+                .clock(clock)
+                .precision(1)
+                .step(0.1)
+                .range(0.0, 1.0)
+                .on_increment(move |ex, amount| {
+                    presses.set(presses.get() + 1);
+                    ex.emit(PriceEvent::Add(amount));
+                })
+                .entity()
+        });
+        let counter = harness.counter();
+        let increment = |harness: &mut CounterHarness| {
+            harness
+                .context()
+                .emit_to(counter, CounterEvent::<f64>::Increment);
+            harness.flush();
+        };
+        for _ in 0..10 {
+            increment(&mut harness);
+        }
+        // モデルでは0.1を10回足した誤差が残っている
+        assert_ne!(Price::value.get(harness.context()), 1.0);
+        // Counterは0.1を1とする整数として保持するため，ちょうど1.0として扱う
+        assert_eq!(harness.display(), "1.0");
+        // 上限に達しているため，端数だけ増やすような11回目の押下は起こらない
+        increment(&mut harness);
+        assert_eq!(presses.get(), 10);
+    }
+
+    #[test]
+    fn display_mode_builds_no_buttons() {
+        let data = AppData::builder().count(4).build().unwrap();
+        let mut harness = CounterHarness::with_view(data, |cx, clock| {
+            Counter::display(cx, AppData::count).clock(clock).entity()
+        });
+        assert_eq!(harness.display(), "4");
+        assert!(harness.find("count").is_some());
+        for class in ["inc", "dec", "reset", "entry"] {
+            assert!(harness.find(class).is_none(), "{} was built", class);
+        }
+    }
+
+    // 増加・減少ボタンをそれぞれ有効にするかどうかを持つモデル
+    #[derive(Lens)]
+    struct Stock {
+        can_add: bool,
+        can_remove: bool,
+    }
+
+    enum StockEvent {
+        SetCanAdd(bool),
+        SetCanRemove(bool),
+    }
+
+    impl Model for Stock {
+        fn event(&mut self, _cx: &mut EventContext, event: &mut Event) {
+            event.map(|stock_event, _| match stock_event {
+                StockEvent::SetCanAdd(enabled) => self.can_add = *enabled,
+                StockEvent::SetCanRemove(enabled) => self.can_remove = *enabled,
+            });
+        }
+    }
+
+    #[test]
+    fn enable_lenses_block_only_their_button() {
+        let mut harness = CounterHarness::with_view(AppData::default(), |cx, clock| {
+            Stock {
+                can_add: true,
+                can_remove: true,
+            }
+            .build(cx);
+            Counter::new(cx, AppData::count)
+                .clock(clock)
+                .increment_enabled(Stock::can_add)
+                .decrement_enabled(Stock::can_remove)
+                .on_increment(|ex, amount| ex.emit(AppEvent::Add(amount)))
+                .on_decrement(|ex, amount| ex.emit(AppEvent::Add(-amount)))
+                .entity()
+        });
+        harness
+            .context()
+            .emit_to(Entity::root(), StockEvent::SetCanAdd(false));
+        harness.flush();
+        harness.increment();
+        harness.decrement();
+        assert_eq!(harness.count(), -1);
+
+        harness
+            .context()
+            .emit_to(Entity::root(), StockEvent::SetCanAdd(true));
+        harness
+            .context()
+            .emit_to(Entity::root(), StockEvent::SetCanRemove(false));
+        harness.flush();
+        harness.decrement();
+        harness.increment();
+        assert_eq!(harness.count(), 0);
+    }
+
+    #[test]
+    fn feedback_distinguishes_a_step_from_a_clamp() {
+        let kinds = Rc::new(RefCell::new(Vec::new()));
+        let data = AppData::builder().min(0).max(3).step(2).build().unwrap();
+        let mut harness = CounterHarness::with_counter(data, |counter| {
+            let kinds = kinds.clone();
+            counter.on_feedback(move |_, kind| kinds.borrow_mut().push(kind))
+        });
+        // 0 -> 2はstepの分だけ，2 -> 3は上限で飽和し，3では既に上限に達している
+        for _ in 0..3 {
+            harness.increment();
+        }
+        harness.send_counter(CounterEvent::Reset);
+        assert_eq!(
+            *kinds.borrow(),
+            [
+                FeedbackKind::Step,
+                FeedbackKind::Clamp,
+                FeedbackKind::Clamp,
+                FeedbackKind::Reset
+            ]
+        );
+    }
+
+    #[test]
+    fn multi_step_buttons_add_their_signed_amount() {
+        let mut harness = CounterHarness::with_view(AppData::default(), |cx, _| {
+            MultiStepCounter::new(cx, AppData::count, vec![1, 10, 100]).entity()
+        });
+        let names = |harness: &mut CounterHarness, class| {
+            harness
+                .find_all(class)
+                .into_iter()
+                .map(|button| harness.name(button).unwrap_or_default())
+                .collect::<Vec<_>>()
+        };
+        // 増加のボタンは符号を付けて"+100"のように表示する
+        assert_eq!(names(&mut harness, "increment"), ["+1", "+10", "+100"]);
+        assert_eq!(names(&mut harness, "decrement"), ["-100", "-10", "-1"]);
+
+        let plus_hundred = harness.find_all("increment")[2];
+        harness.press(plus_hundred);
+        assert_eq!(harness.count(), 100);
+        let minus_ten = harness.find_all("decrement")[1];
+        harness.press(minus_ten);
+        assert_eq!(harness.count(), 90);
+    }
+
+    #[test]
+    fn dirty_follows_mark_clean() {
+        let mut harness = CounterHarness::new(AppData::default());
+        harness.increment();
+        assert!(harness.snapshot().dirty);
+        harness.send_app(AppEvent::MarkClean);
+        assert!(!harness.snapshot().dirty);
+        harness.decrement();
+        assert!(harness.snapshot().dirty);
+    }
+
+    #[test]
+    fn save_marks_clean_only_when_the_file_is_written() {
+        let path = temp_path("save-button");
+        let mut harness = CounterHarness::new(AppData {
+            save_path: Some(path.clone()),
+            ..AppData::default()
+        });
+        harness.increment();
+        harness.send_app(AppEvent::Save);
+        assert!(!harness.snapshot().dirty);
+        assert_eq!(AppData::load(&path).count, 1);
+        fs::remove_file(&path).unwrap();
+
+        // 存在しないディレクトリには書き込めないため，変更されたままとする
+        let missing = std::env::temp_dir()
+            .join(format!("vizia-lab-missing-{}", std::process::id()))
+            .join("counter.json");
+        let mut harness = CounterHarness::new(AppData {
+            save_path: Some(missing),
+            ..AppData::default()
+        });
+        harness.increment();
+        harness.send_app(AppEvent::Save);
+        assert!(harness.snapshot().dirty);
+    }
+
+    #[test]
+    fn value_width_reserves_a_minimum_width() {
+        let mut harness = CounterHarness::new(AppData::default());
+        let label = harness.find("count").unwrap();
+        assert_eq!(harness.min_width(label), Some(Units::Auto));
+
+        let mut harness =
+            CounterHarness::with_counter(AppData::default(), |counter| counter.value_width(3));
+        let label = harness.find("count").unwrap();
+        let width = Units::Pixels(3.0 * VALUE_CHAR_WIDTH);
+        assert_eq!(harness.min_width(label), Some(width));
+        // 桁数が変わってもLabelは同じ幅を保つ
+        for _ in 0..10 {
+            harness.increment();
+        }
+        let label = harness.find("count").unwrap();
+        assert_eq!(harness.min_width(label), Some(width));
+    }
+
+    #[test]
+    fn rapid_changes_are_saved_once_after_the_delay() {
+        let path = temp_path("save-delay");
+        let _ = fs::remove_file(&path);
+        let data = AppData {
+            save_path: Some(path.clone()),
+            ..AppData::default()
+        };
+        let mut harness = CounterHarness::with_data(
+            |cx| data.save_delay(cx, Duration::from_millis(500)),
+            |cx, clock| {
+                Counter::new(cx, AppData::count)
+                    .clock(clock)
+                    .on_increment(|ex, amount| ex.emit(AppEvent::Add(amount)))
+                    .entity()
+            },
+        );
+        for _ in 0..3 {
+            harness.increment();
+            harness.advance(Duration::from_millis(100));
+        }
+        // 最後の変更からsave_delayが経つまでは書き込まない
+        assert!(!path.exists());
+
+        // save_delayのタイマーが経過したときと同じようにFlushSaveを送る
+        harness.send_app(AppEvent::FlushSave);
+        assert_eq!(AppData::load(&path).count, 3);
+        fs::remove_file(&path).unwrap();
+        // 書き込んだ後は保存を待っている変更がないため，再び書き込むことはない
+        harness.send_app(AppEvent::FlushSave);
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn focused_class_follows_focus_within_the_counter() {
+        let mut harness = CounterHarness::new(AppData::default());
+        let counter = harness.counter();
+        assert!(!harness.has_class(counter, "focused"));
+        harness.send_window(WindowEvent::FocusIn);
+        assert!(harness.has_class(counter, "focused"));
+        // 内部の要素の間でフォーカスが移る間もクラスは外さない
+        harness.context().emit_to(counter, WindowEvent::FocusOut);
+        harness.context().emit_to(counter, WindowEvent::FocusIn);
+        harness.flush();
+        assert!(harness.has_class(counter, "focused"));
+        harness.send_window(WindowEvent::FocusOut);
+        assert!(!harness.has_class(counter, "focused"));
+    }
+
+    #[test]
+    fn a_batch_is_undone_in_one_step() {
+        let mut data = AppData::builder().count(3).build().unwrap();
+        data.apply_batch(10);
+        assert_eq!(data.count, 13);
+        assert_eq!(data.history, vec![3]);
+
+        let mut harness = CounterHarness::new(data);
+        harness.send_app(AppEvent::Undo);
+        assert_eq!(harness.count(), 3);
+        assert!(harness.snapshot().history.is_empty());
+    }
+
+    #[test]
+    fn reset_returns_to_the_default_value() {
+        let data = AppData::builder().count(5).build().unwrap();
+        let mut harness = CounterHarness::with_counter(data, |counter| counter.default_value(2));
+        harness.send_counter(CounterEvent::Reset);
+        assert_eq!(harness.count(), 2);
+        // default_valueを設定しない場合は0に戻す
+        let data = AppData::builder().count(5).build().unwrap();
+        let mut harness = CounterHarness::new(data);
+        harness.send_counter(CounterEvent::Reset);
+        assert_eq!(harness.count(), 0);
+    }
+
+    #[test]
+    fn custom_content_replaces_the_button_label() {
+        let mut harness = CounterHarness::with_counter(AppData::default(), |counter| {
+            counter.increment_content(|cx| {
+                Label::new(cx, "\u{25B2}").class("arrow");
+            })
+        });
+        assert!(harness.read(CounterText::increment_custom));
+        assert!(!harness.read(CounterText::decrement_custom));
+        // 増加ボタンの中身だけが置き換わる
+        assert_eq!(harness.find_all("arrow").len(), 1);
+        assert_eq!(
+            harness.class_order(&["dec", "inc", "arrow"]),
+            ["dec", "inc", "arrow"]
+        );
+    }
+
+    #[test]
+    fn page_keys_step_by_the_page_step() {
+        let data = AppData::builder().count(50).build().unwrap();
+        let mut harness = CounterHarness::new(data);
+        // page_stepを設定しない場合はstepの10倍
+        harness.key_down(Code::PageUp);
+        assert_eq!(harness.count(), 60);
+        harness.key_down(Code::PageDown);
+        harness.key_down(Code::PageDown);
+        assert_eq!(harness.count(), 40);
+
+        let data = AppData::builder().count(50).max(70).build().unwrap();
+        let mut harness = CounterHarness::with_counter(data, |counter| counter.page_step(25));
+        harness.key_down(Code::PageDown);
+        assert_eq!(harness.count(), 25);
+        // 上限を超える分は飽和させる
+        harness.key_down(Code::PageUp);
+        harness.key_down(Code::PageUp);
+        assert_eq!(harness.count(), 70);
+    }
+
+    #[test]
+    fn total_sums_every_count() {
+        let mut harness = CounterHarness::new(AppData::default());
+        assert_eq!(AppData::counts_total().get(harness.context()), 0);
+        harness.send_app(AppEvent::IncrementAt(0, 2));
+        harness.send_app(AppEvent::IncrementAt(2, 3));
+        assert_eq!(AppData::counts_total().get(harness.context()), 5);
+        harness.send_app(AppEvent::DecrementAt(0, 1));
+        assert_eq!(AppData::counts_total().get(harness.context()), 4);
+    }
+
+    #[test]
+    fn before_step_keeps_the_count_even() {
+        let mut harness = CounterHarness::with_counter(AppData::default(), |counter| {
+            counter
+                .step_lens(AppData::step)
+                .before_step(|cx, direction, amount| {
+                    let count = AppData::count.get(cx);
+                    let next = match direction {
+                        StepDirection::Increment => count + amount,
+                        StepDirection::Decrement => count - amount,
+                    };
+                    next % 2 == 0
+                })
+        });
+        let mut seen = Vec::new();
+        harness.increment();
+        seen.push(harness.count());
+        harness.send_app(AppEvent::AddStep(1));
+        harness.increment();
+        seen.push(harness.count());
+        harness.increment();
+        seen.push(harness.count());
+        harness.send_app(AppEvent::AddStep(-1));
+        harness.decrement();
+        seen.push(harness.count());
+        // stepが1のときの変化は奇数になるため取り消され，stepが2のときだけ変化する
+        assert_eq!(seen, [0, 2, 4, 4]);
+    }
+
+    // 入力パッドのボタンはnumpad-keyのクラスを1,2,...,9,Backspace,0,OKの順に持つ
+    fn numpad_key(harness: &mut CounterHarness, label: &str) -> Entity {
+        let index = match label {
+            "OK" => 11,
+            "0" => 10,
+            digit => digit.parse::<usize>().unwrap() - 1,
+        };
+        harness.find_all("numpad-key")[index]
+    }
+
+    // 値のLabelをドラッグせずにタップしたときと同じように入力パッドを開く
+    fn open_numpad(harness: &mut CounterHarness) {
+        harness.send_counter(CounterEvent::ScrubStart(0.0));
+        harness.send_counter(CounterEvent::ScrubEnd);
+        assert!(harness.read(CounterLayoutState::numpad_open));
+    }
+
+    #[test]
+    fn numpad_enters_a_value_with_ok() {
+        let data = AppData::builder().max(100).build().unwrap();
+        let mut harness = CounterHarness::with_counter(data, |counter| counter.numpad(true));
+        open_numpad(&mut harness);
+        for label in ["2", "5", "OK"] {
+            let key = numpad_key(&mut harness, label);
+            harness.press(key);
+        }
+        assert_eq!(harness.count(), 25);
+        assert!(!harness.read(CounterLayoutState::numpad_open));
+    }
+
+    #[test]
+    fn numpad_cancel_leaves_the_value_unchanged() {
+        let data = AppData::builder().max(100).build().unwrap();
+        let mut harness = CounterHarness::with_counter(data, |counter| counter.numpad(true));
+        open_numpad(&mut harness);
+        let key = numpad_key(&mut harness, "7");
+        harness.press(key);
+        let cancel = harness.find("numpad-cancel").unwrap();
+        harness.press(cancel);
+        assert_eq!(harness.count(), 0);
+        assert!(!harness.read(CounterLayoutState::numpad_open));
+    }
+
+    #[test]
+    fn style_scope_adds_the_scope_class() {
+        let mut harness =
+            CounterHarness::with_counter(AppData::default(), |counter| counter.style_scope("goal"));
+        let counter = harness.counter();
+        assert!(harness.has_class(counter, "scope-goal"));
+        assert!(!harness.has_class(counter, "scope-other"));
+    }
+
+    #[test]
+    fn inline_edit_commits_the_entered_value() {
+        let data = AppData::builder().count(3).max(10).build().unwrap();
+        let mut harness = CounterHarness::with_counter(data, |counter| counter.inline_edit(true));
+        harness.send_counter(CounterEvent::StartInlineEdit);
+        assert!(harness.read(CounterLayoutState::editing));
+        assert!(harness.find("inline-entry").is_some());
+        // 範囲外の値は受け付けず，編集を続ける
+        harness.send_counter(CounterEvent::SubmitInline(String::from("42")));
+        assert_eq!(harness.count(), 3);
+        assert!(harness.read(CounterLayoutState::editing));
+        harness.send_counter(CounterEvent::SubmitInline(String::from("7")));
+        assert_eq!(harness.count(), 7);
+        assert!(!harness.read(CounterLayoutState::editing));
+        assert!(harness.find("inline-entry").is_none());
+    }
+
+    #[test]
+    fn inline_edit_cancel_reverts_to_the_label() {
+        let data = AppData::builder().count(3).max(10).build().unwrap();
+        let mut harness = CounterHarness::with_counter(data, |counter| counter.inline_edit(true));
+        harness.send_counter(CounterEvent::StartInlineEdit);
+        let counter = harness.counter();
+        harness
+            .context()
+            .emit_to(counter, CounterTextEvent::SetEntry(String::from("9")));
+        harness.flush();
+        harness.send_counter(CounterEvent::CancelInlineEdit);
+        assert_eq!(harness.count(), 3);
+        assert_eq!(harness.read(CounterText::entry), "3");
+        assert!(!harness.read(CounterLayoutState::editing));
+        assert!(harness.find("inline-entry").is_none());
+    }
+
+    #[test]
+    fn inline_edit_is_off_by_default() {
+        let mut harness = CounterHarness::new(AppData::default());
+        harness.send_counter(CounterEvent::StartInlineEdit);
+        assert!(!harness.read(CounterLayoutState::editing));
+    }
+
+    #[test]
+    fn handle_emit_helpers_fire_the_callbacks() {
+        let steps = Rc::new(RefCell::new(Vec::new()));
+        let recorded = steps.clone();
+        let harness = CounterHarness::with_view(AppData::default(), move |cx, clock| {
+            let increments = recorded.clone();
+            let decrements = recorded;
+            let mut counter = Counter::new(cx, AppData::count)
+                .clock(clock)
+                .range(0, 10)
+                .on_increment(move |ex, amount| {
+                    increments.borrow_mut().push(amount);
+                    ex.emit(AppEvent::Add(amount));
+                })
+                .on_decrement(move |ex, amount| {
+                    decrements.borrow_mut().push(-amount);
+                    ex.emit(AppEvent::Add(-amount));
+                });
+            counter.emit_increment();
+            counter.emit_increment();
+            counter.emit_decrement();
+            counter.entity()
+        });
+        assert_eq!(*steps.borrow(), vec![1, 1, -1]);
+        assert_eq!(harness.count(), 1);
+    }
+
+    #[test]
+    fn leading_icon_is_built_before_the_value() {
+        let mut harness = CounterHarness::with_counter(AppData::default(), |counter| {
+            counter.leading_icon(|cx| {
+                Label::new(cx, "\u{2665}").class("heart");
+            })
+        });
+        assert!(harness.find("leading-icon").is_some());
+        assert!(harness.find("heart").is_some());
+        assert_eq!(
+            harness.class_order(&["leading-icon", "value"]),
+            vec!["leading-icon", "value"]
+        );
+    }
+
+    #[test]
+    fn no_leading_icon_by_default() {
+        let mut harness = CounterHarness::new(AppData::default());
+        assert!(harness.find("leading-icon").is_none());
+    }
+
+    #[test]
+    fn accent_color_sets_the_button_background_inline() {
+        let accent = Color::rgb(200, 40, 90);
+        let mut harness = CounterHarness::with_counter(AppData::default(), |counter| {
+            counter.accent_color(accent)
+        });
+        assert_eq!(harness.read(CounterLayoutState::accent), Some(accent));
+        for class in ["inc", "dec"] {
+            let button = harness.find(class).unwrap();
+            assert_eq!(harness.background_color(button), Some(accent));
+        }
+    }
+
+    #[test]
+    fn buttons_keep_the_stylesheet_color_without_an_accent() {
+        let mut harness = CounterHarness::new(AppData::default());
+        let button = harness.find("inc").unwrap();
+        assert_eq!(harness.background_color(button), None);
+    }
+
+    #[test]
+    fn compact_applies_its_class_and_the_compact_layout() {
+        let mut harness = CounterHarness::with_view(AppData::default(), |cx, clock| {
+            Counter::compact(cx, AppData::count)
+                .clock(clock)
+                .on_increment(|ex, amount| ex.emit(AppEvent::Add(amount)))
+                .entity()
+        });
+        let counter = harness.counter();
+        assert!(harness.has_class(counter, "compact"));
+        assert!(!harness.has_class(counter, "pill"));
+        assert!(harness.read(CounterLayoutState::compact));
+        // 減少ボタン，値，増加ボタンだけを隙間なく並べる
+        assert_eq!(
+            harness.class_order(&["dec", "value", "inc", "entry", "reset"]),
+            vec!["dec", "value", "inc"]
+        );
+        // 並びが小さくなってもイベントの処理はCounterと同じ
+        harness.increment();
+        assert_eq!(harness.count(), 1);
+    }
+}
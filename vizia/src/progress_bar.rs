@@ -0,0 +1,54 @@
+use vizia::prelude::*;
+
+// 値が最大値に対してどれだけ進んでいるかを横棒で表示するビュー
+// Lensにバインドしているため，値が変化すると塗りつぶしの幅も更新される
+pub struct ProgressBar;
+
+// 値と最大値から塗りつぶす割合を0.0から1.0の範囲で求める
+// 値が0未満や最大値を超える場合は0.0と1.0に丸め，最大値が0以下の場合は常に0.0とする
+pub fn fill_ratio(value: i32, max: i32) -> f32 {
+    if max <= 0 {
+        return 0.0;
+    }
+    (value as f32 / max as f32).clamp(0.0, 1.0)
+}
+
+impl View for ProgressBar {
+    fn element(&self) -> Option<&'static str> {
+        Some("progressbar")
+    }
+}
+
+impl ProgressBar {
+    pub fn new<L>(cx: &mut Context, lens: L, max: i32) -> Handle<Self>
+    where
+        L: Lens<Target = i32>,
+    {
+        Self.build(cx, |cx| {
+            // 塗りつぶし部分の幅を割合から百分率で指定する
+            Element::new(cx)
+                .width(lens.map(move |value| Percentage(fill_ratio(*value, max) * 100.0)))
+                .class("fill");
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fill_ratio_covers_the_range() {
+        assert_eq!(fill_ratio(0, 10), 0.0);
+        assert_eq!(fill_ratio(5, 10), 0.5);
+        assert_eq!(fill_ratio(10, 10), 1.0);
+    }
+
+    #[test]
+    fn fill_ratio_clamps_values_outside_the_range() {
+        assert_eq!(fill_ratio(-3, 10), 0.0);
+        assert_eq!(fill_ratio(15, 10), 1.0);
+        // 最大値が0以下の場合は塗りつぶさない
+        assert_eq!(fill_ratio(5, 0), 0.0);
+    }
+}
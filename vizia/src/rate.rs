@@ -0,0 +1,108 @@
+// 直近の一定時間に値がどれだけ変化したかを求めるためのモジュール
+// 変化するたびに時刻と変化量を記録し，時間窓より古い記録は取り除く
+// 時刻はCounterの時計から受け取るため，テストのMockClockでも確かめられる
+use std::collections::VecDeque;
+use std::ops::{Add, Sub};
+use std::time::{Duration, Instant};
+
+use num_traits::One;
+
+pub struct RateTracker<T> {
+    // 変化量を合計する時間の長さ
+    window: Duration,
+    // 変化した時刻と変化量．古い順に並べて保持する
+    changes: VecDeque<(Instant, T)>,
+}
+
+impl<T> RateTracker<T>
+where
+    T: Copy + Add<Output = T> + Sub<Output = T> + One,
+{
+    pub fn new(window: Duration) -> Self {
+        Self {
+            window,
+            changes: VecDeque::new(),
+        }
+    }
+
+    pub fn window(&self) -> Duration {
+        self.window
+    }
+
+    // 記録が残っているかどうか．残っていない間は古い記録を取り除く必要がない
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    // nowの時点での変化量を記録する
+    pub fn record(&mut self, now: Instant, delta: T) {
+        self.changes.push_back((now, delta));
+        self.prune(now);
+    }
+
+    // 時間窓より古い記録を取り除く
+    pub fn prune(&mut self, now: Instant) {
+        while let Some((time, _)) = self.changes.front() {
+            if now.duration_since(*time) > self.window {
+                self.changes.pop_front();
+            } else {
+                break;
+            }
+        }
+    }
+
+    // nowまでの時間窓の中での変化量の合計を返す
+    pub fn net(&mut self, now: Instant) -> T {
+        self.prune(now);
+        self.changes
+            .iter()
+            .fold(T::one() - T::one(), |total, (_, delta)| total + *delta)
+    }
+}
+
+// "+3 in the last 5s"のように表示する文字列を作る
+// 増加した場合は符号を付けて，減少した場合と区別できるようにする
+pub fn rate_text<T>(net: T, window: Duration) -> String
+where
+    T: Copy + PartialOrd + One + Sub<Output = T> + std::fmt::Display,
+{
+    let sign = if net > T::one() - T::one() { "+" } else { "" };
+    format!("{}{} in the last {}", sign, net, window_text(window))
+}
+
+// 時間窓を"5s"や"500ms"のように表す
+fn window_text(window: Duration) -> String {
+    if window.subsec_millis() == 0 {
+        format!("{}s", window.as_secs())
+    } else {
+        format!("{}ms", window.as_millis())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sums_changes_inside_the_window() {
+        let start = Instant::now();
+        let mut tracker = RateTracker::new(Duration::from_secs(5));
+        tracker.record(start, 3);
+        tracker.record(start + Duration::from_secs(2), -1);
+        assert_eq!(tracker.net(start + Duration::from_secs(4)), 2);
+        // 最初の記録が時間窓から外れると，その分だけ減る
+        assert_eq!(tracker.net(start + Duration::from_secs(6)), -1);
+        assert_eq!(tracker.net(start + Duration::from_secs(8)), 0);
+        assert!(tracker.is_empty());
+    }
+
+    #[test]
+    fn formats_the_net_change() {
+        assert_eq!(rate_text(3, Duration::from_secs(5)), "+3 in the last 5s");
+        assert_eq!(rate_text(-2, Duration::from_secs(5)), "-2 in the last 5s");
+        assert_eq!(
+            rate_text(0, Duration::from_millis(1500)),
+            "0 in the last 1500ms"
+        );
+    }
+}
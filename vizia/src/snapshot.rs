@@ -0,0 +1,180 @@
+// AppDataの状態を写し取って比較するためのモジュール
+// 操作の前後でスナップショットを取り，diffで変わったフィールドだけを確かめることで，
+// 複雑な操作を確認するテストでも何が変わったかを読みやすく書ける
+// タイマーやコールバックは比較できないため含めない
+use std::fmt::Debug;
+use std::path::PathBuf;
+
+use crate::{AppData, LayoutDirection, OverflowPolicy, Theme};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct AppDataSnapshot {
+    pub count: i32,
+    pub initial: i32,
+    pub dirty: bool,
+    pub min: i32,
+    pub max: i32,
+    pub step: i32,
+    pub weight_scaled: i64,
+    pub counts: Vec<i32>,
+    pub rooms: u32,
+    pub hour: i32,
+    pub locked: bool,
+    pub theme: Theme,
+    pub direction: LayoutDirection,
+    pub display_window: bool,
+    pub overflow_policy: OverflowPolicy,
+    pub history: Vec<i32>,
+    pub redo: Vec<i32>,
+    pub save_path: Option<PathBuf>,
+    pub save_pending: bool,
+    pub pending: Vec<i32>,
+    pub drag_origin: Option<i32>,
+}
+
+// 2つのスナップショットで値が異なっていたフィールド
+// 値はDebugで整形した文字列として持つ
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldChange {
+    pub field: &'static str,
+    pub before: String,
+    pub after: String,
+}
+
+impl AppData {
+    // 現在の状態を写し取る
+    pub fn snapshot(&self) -> AppDataSnapshot {
+        AppDataSnapshot {
+            count: self.count,
+            initial: self.initial,
+            dirty: self.dirty,
+            min: self.min,
+            max: self.max,
+            step: self.step,
+            weight_scaled: self.weight_scaled,
+            counts: self.counts.clone(),
+            rooms: self.rooms,
+            hour: self.hour,
+            locked: self.locked,
+            theme: self.theme,
+            direction: self.direction,
+            display_window: self.display_window,
+            overflow_policy: self.overflow_policy,
+            history: self.history.clone(),
+            redo: self.redo.clone(),
+            save_path: self.save_path.clone(),
+            save_pending: self.save_pending,
+            pending: self.pending.clone(),
+            drag_origin: self.drag_origin,
+        }
+    }
+}
+
+// 値が異なる場合だけchangesに加える
+fn compare<T: Debug + PartialEq>(
+    changes: &mut Vec<FieldChange>,
+    field: &'static str,
+    before: &T,
+    after: &T,
+) {
+    if before != after {
+        changes.push(FieldChange {
+            field,
+            before: format!("{:?}", before),
+            after: format!("{:?}", after),
+        });
+    }
+}
+
+impl AppDataSnapshot {
+    // selfを変更前，otherを変更後として，値が変わったフィールドを宣言順に返す
+    // 例: count: 0 -> 1 の操作では，countのほかにcountの変更を記録するhistoryも含まれる
+    pub fn diff(&self, other: &AppDataSnapshot) -> Vec<FieldChange> {
+        let mut changes = Vec::new();
+        compare(&mut changes, "count", &self.count, &other.count);
+        compare(&mut changes, "initial", &self.initial, &other.initial);
+        compare(&mut changes, "dirty", &self.dirty, &other.dirty);
+        compare(&mut changes, "min", &self.min, &other.min);
+        compare(&mut changes, "max", &self.max, &other.max);
+        compare(&mut changes, "step", &self.step, &other.step);
+        compare(
+            &mut changes,
+            "weight_scaled",
+            &self.weight_scaled,
+            &other.weight_scaled,
+        );
+        compare(&mut changes, "counts", &self.counts, &other.counts);
+        compare(&mut changes, "rooms", &self.rooms, &other.rooms);
+        compare(&mut changes, "hour", &self.hour, &other.hour);
+        compare(&mut changes, "locked", &self.locked, &other.locked);
+        compare(&mut changes, "theme", &self.theme, &other.theme);
+        compare(&mut changes, "direction", &self.direction, &other.direction);
+        compare(
+            &mut changes,
+            "display_window",
+            &self.display_window,
+            &other.display_window,
+        );
+        compare(
+            &mut changes,
+            "overflow_policy",
+            &self.overflow_policy,
+            &other.overflow_policy,
+        );
+        compare(&mut changes, "history", &self.history, &other.history);
+        compare(&mut changes, "redo", &self.redo, &other.redo);
+        compare(&mut changes, "save_path", &self.save_path, &other.save_path);
+        compare(
+            &mut changes,
+            "save_pending",
+            &self.save_pending,
+            &other.save_pending,
+        );
+        compare(&mut changes, "pending", &self.pending, &other.pending);
+        compare(
+            &mut changes,
+            "drag_origin",
+            &self.drag_origin,
+            &other.drag_origin,
+        );
+        changes
+    }
+
+    // 変わったフィールドの名前だけを返す
+    pub fn changed_fields(&self, other: &AppDataSnapshot) -> Vec<&'static str> {
+        self.diff(other)
+            .into_iter()
+            .map(|change| change.field)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::testing::CounterHarness;
+    use crate::AppData;
+
+    #[test]
+    fn an_increment_changes_only_the_count() {
+        let mut harness = CounterHarness::new(AppData::default());
+        let before = harness.snapshot();
+        harness.increment();
+        let after = harness.snapshot();
+        // dirtyとhistoryはcountの変更に伴って更新される派生した状態であり，利用者が操作した値ではない
+        // それらを除くと，変わったのはcountだけとなる
+        let changed: Vec<_> = before
+            .changed_fields(&after)
+            .into_iter()
+            .filter(|field| !matches!(*field, "dirty" | "history"))
+            .collect();
+        assert_eq!(changed, vec!["count"]);
+        let count = &before.diff(&after)[0];
+        assert_eq!((count.before.as_str(), count.after.as_str()), ("0", "1"));
+    }
+
+    #[test]
+    fn identical_snapshots_have_no_diff() {
+        let harness = CounterHarness::new(AppData::default());
+        assert!(harness.snapshot().diff(&harness.snapshot()).is_empty());
+    }
+}
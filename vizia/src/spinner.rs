@@ -0,0 +1,63 @@
+use vizia::prelude::*;
+
+use crate::{Counter, CounterModifiers, CounterOrientation, CounterValue};
+
+// Counterを組み合わせたフォーム向けの数値入力
+// 枠で囲った値の上下に小さな矢印のボタンを配置する
+// イベントやコールバックの仕組みはCounterのものをそのまま使用するため，
+// Spinner::newはHandle<Counter<T>>を返し，on_increment/on_decrementやrange，stepなどのモディファイアを使用できる
+pub struct Spinner;
+
+impl Spinner {
+    pub fn new<T, L>(cx: &mut Context, lens: L) -> Handle<Counter<T>>
+    where
+        T: CounterValue,
+        L: Lens<Target = T>,
+    {
+        // 増加ボタンを上にした縦の並びを使用し，見た目はspinnerクラスでCSSから指定する
+        Counter::new(cx, lens)
+            .orientation(CounterOrientation::Vertical)
+            .button_labels("▲", "▼")
+            .class("spinner")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::CounterHarness;
+    use crate::{AppData, AppEvent, CounterEvent, StepDirection};
+
+    fn harness() -> CounterHarness {
+        let data = AppData::builder().min(0).max(2).build().unwrap();
+        CounterHarness::with_view(data, |cx, clock| {
+            Spinner::new(cx, AppData::count)
+                .clock(clock)
+                .range(0, 2)
+                .on_increment(|ex, amount| ex.emit(AppEvent::Add(amount)))
+                .on_decrement(|ex, amount| ex.emit(AppEvent::Add(-amount)))
+                .entity()
+        })
+    }
+
+    // ▲・▼のボタンを押して離したときにCounterへ送られるイベントと同じものを送る
+    fn press(harness: &mut CounterHarness, direction: StepDirection) {
+        harness.send_counter(CounterEvent::StartRepeat(direction));
+        harness.send_counter(CounterEvent::StopRepeat);
+    }
+
+    #[test]
+    fn arrows_step_and_stop_at_the_bounds() {
+        let mut harness = harness();
+        assert!(harness.find("inc").is_some());
+        assert!(harness.find("dec").is_some());
+        for _ in 0..3 {
+            press(&mut harness, StepDirection::Increment);
+        }
+        assert_eq!(harness.count(), 2);
+        for _ in 0..3 {
+            press(&mut harness, StepDirection::Decrement);
+        }
+        assert_eq!(harness.count(), 0);
+    }
+}
@@ -0,0 +1,163 @@
+use std::collections::VecDeque;
+use std::sync::{Arc, Condvar, Mutex};
+
+// Counterの値の変化を別のスレッドなどで順に受け取るためのチャネル
+// 送信側はUIスレッドで使われるため，受信側が遅くても待たずに済むように容量を超えた場合は最も古い値を捨てる
+// 受信側は別のスレッドに渡すことができ，recvは値が届くか送信側がすべて破棄されるまで待つ
+
+struct Shared<T> {
+    queue: Mutex<State<T>>,
+    available: Condvar,
+}
+
+struct State<T> {
+    values: VecDeque<T>,
+    capacity: usize,
+    // 送信側と受信側の残りの数．どちらかが0になったら，もう一方は相手がいないと判断する
+    senders: usize,
+    receiver_alive: bool,
+}
+
+pub struct ChangeSender<T> {
+    shared: Arc<Shared<T>>,
+}
+
+pub struct ChangeReceiver<T> {
+    shared: Arc<Shared<T>>,
+}
+
+// 最大capacity個の値を保持するチャネルを作る．capacityが0の場合は1とする
+pub fn channel<T>(capacity: usize) -> (ChangeSender<T>, ChangeReceiver<T>) {
+    let shared = Arc::new(Shared {
+        queue: Mutex::new(State {
+            values: VecDeque::new(),
+            capacity: capacity.max(1),
+            senders: 1,
+            receiver_alive: true,
+        }),
+        available: Condvar::new(),
+    });
+    (
+        ChangeSender {
+            shared: shared.clone(),
+        },
+        ChangeReceiver { shared },
+    )
+}
+
+impl<T> ChangeSender<T> {
+    // 値を送る．いっぱいの場合は最も古い値を捨ててから追加するため，待つことはない
+    // 受信側が破棄されている場合はfalseを返す
+    pub fn send(&self, value: T) -> bool {
+        let mut state = self.shared.queue.lock().unwrap();
+        if !state.receiver_alive {
+            return false;
+        }
+        if state.values.len() >= state.capacity {
+            state.values.pop_front();
+        }
+        state.values.push_back(value);
+        self.shared.available.notify_one();
+        true
+    }
+}
+
+impl<T> Clone for ChangeSender<T> {
+    fn clone(&self) -> Self {
+        self.shared.queue.lock().unwrap().senders += 1;
+        Self {
+            shared: self.shared.clone(),
+        }
+    }
+}
+
+impl<T> Drop for ChangeSender<T> {
+    fn drop(&mut self) {
+        let mut state = self.shared.queue.lock().unwrap();
+        state.senders -= 1;
+        // 待っている受信側が終了できるように起こす
+        if state.senders == 0 {
+            self.shared.available.notify_all();
+        }
+    }
+}
+
+impl<T> ChangeReceiver<T> {
+    // 値が届くまで待って受け取る．送信側がすべて破棄され，残りの値もない場合はNoneを返す
+    pub fn recv(&self) -> Option<T> {
+        let mut state = self.shared.queue.lock().unwrap();
+        loop {
+            if let Some(value) = state.values.pop_front() {
+                return Some(value);
+            }
+            if state.senders == 0 {
+                return None;
+            }
+            state = self.shared.available.wait(state).unwrap();
+        }
+    }
+
+    // 届いている値があれば受け取る．待つことはない
+    pub fn try_recv(&self) -> Option<T> {
+        self.shared.queue.lock().unwrap().values.pop_front()
+    }
+}
+
+impl<T> Drop for ChangeReceiver<T> {
+    fn drop(&mut self) {
+        self.shared.queue.lock().unwrap().receiver_alive = false;
+    }
+}
+
+// for文で値を順に受け取れるようにする．送信側がすべて破棄されると終了する
+impl<T> Iterator for ChangeReceiver<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        self.recv()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    #[test]
+    fn drops_the_oldest_value_when_full() {
+        let (sender, receiver) = channel(2);
+        assert!(sender.send(1));
+        assert!(sender.send(2));
+        assert!(sender.send(3));
+        assert_eq!(receiver.try_recv(), Some(2));
+        assert_eq!(receiver.try_recv(), Some(3));
+        assert_eq!(receiver.try_recv(), None);
+    }
+
+    #[test]
+    fn zero_capacity_keeps_the_latest_value() {
+        let (sender, receiver) = channel(0);
+        sender.send(1);
+        sender.send(2);
+        assert_eq!(receiver.try_recv(), Some(2));
+    }
+
+    #[test]
+    fn send_fails_after_the_receiver_is_dropped() {
+        let (sender, receiver) = channel(4);
+        drop(receiver);
+        assert!(!sender.send(1));
+    }
+
+    #[test]
+    fn iteration_ends_when_every_sender_is_dropped() {
+        let (sender, receiver) = channel(4);
+        let other = sender.clone();
+        let handle = thread::spawn(move || receiver.collect::<Vec<i32>>());
+        sender.send(1);
+        other.send(2);
+        drop(sender);
+        drop(other);
+        assert_eq!(handle.join().unwrap(), vec![1, 2]);
+    }
+}
@@ -0,0 +1,330 @@
+// ウィンドウを開かずにCounterの動作を確認するためのユーティリティ
+// Applicationを起動せずにContextを直接作り，AppDataとCounterを追加する
+// 合成したイベントを送ってからイベントを処理し，その後AppDataの値を読み出すことができる
+// `cargo test`でテストをビルドするときだけコンパイルされる
+use std::rc::Rc;
+use std::time::Duration;
+
+use vizia::prelude::*;
+use vizia_core::backend::BackendContext;
+use vizia_core::events::EventManager;
+
+use crate::clock::MockClock;
+use crate::snapshot::AppDataSnapshot;
+use crate::{
+    AppData, AppEvent, Counter, CounterEvent, CounterModifiers, CounterState, CounterText,
+};
+
+// イベントの処理とデータの更新を繰り返す回数
+// Lensの変化はデータの更新でBindingに伝わり，CounterがValueChangedを送ってコールバックを呼び出すため，
+// 1回のイベントが数回の往復を経てから落ち着く
+const FLUSH_ROUNDS: usize = 8;
+
+pub struct CounterHarness {
+    cx: Context,
+    events: EventManager,
+    counter: Entity,
+    // Counterが使う時計．advanceを呼び出したときだけ進む
+    clock: Rc<MockClock>,
+}
+
+impl CounterHarness {
+    // 指定したAppDataと，main()と同じようにAppEventを送るCounterを持つContextを作る
+    pub fn new(data: AppData) -> Self {
+        Self::with_counter(data, |counter| counter)
+    }
+
+    // configureでnewのCounterに追加の設定を行ってからContextを作る
+    // preview_on_holdのように，newのCounterでは有効になっていない動作を確かめるときに使う
+    pub fn with_counter(
+        data: AppData,
+        configure: impl FnOnce(Handle<Counter<i32>>) -> Handle<Counter<i32>>,
+    ) -> Self {
+        let (min, max, step) = (data.min, data.max, data.step);
+        Self::with_view(data, |cx, clock| {
+            let counter = Counter::new(cx, AppData::count).clock(clock);
+            configure(counter)
+                .range(min, max)
+                .step(step)
+                .on_increment(|ex, amount| ex.emit(AppEvent::Add(amount)))
+                .on_decrement(|ex, amount| ex.emit(AppEvent::Add(-amount)))
+                .on_reset(|ex| ex.emit(AppEvent::Reset))
+                .on_set(|ex, value| ex.emit(AppEvent::SetValue(value)))
+                .entity()
+        })
+    }
+
+    // buildで任意のビューを作ったContextを作る．buildは操作の対象とするCounterのEntityを返す
+    // AppData::count以外にバインドしたCounterや，Counterを子に持つビューを確かめるときに使う
+    pub fn with_view(
+        data: AppData,
+        build: impl FnOnce(&mut Context, Rc<MockClock>) -> Entity,
+    ) -> Self {
+        Self::with_data(|_| data, build)
+    }
+
+    // dataでContextを使ってAppDataを作ってから，with_viewと同じようにContextを作る
+    // save_delayのように，作成するときにContextを必要とするAppDataを確かめるときに使う
+    pub fn with_data(
+        data: impl FnOnce(&mut Context) -> AppData,
+        build: impl FnOnce(&mut Context, Rc<MockClock>) -> Entity,
+    ) -> Self {
+        let mut cx = Context::default();
+        data(&mut cx).build(&mut cx);
+
+        let clock = Rc::new(MockClock::new());
+        let counter = build(&mut cx, clock.clone());
+
+        let mut harness = Self {
+            cx,
+            events: EventManager::new(),
+            counter,
+            clock,
+        };
+        // 構築時に送られたイベントを処理しておく
+        harness.flush();
+        harness
+    }
+
+    // 操作の対象としているCounter
+    pub fn counter(&self) -> Entity {
+        self.counter
+    }
+
+    // Counterにイベントを送り，処理が終わるまで進める
+    pub fn send_counter(&mut self, event: CounterEvent<i32>) {
+        self.cx.emit_to(self.counter, event);
+        self.flush();
+    }
+
+    // AppDataにイベントを送り，処理が終わるまで進める
+    pub fn send_app(&mut self, event: AppEvent) {
+        self.cx.emit_to(Entity::root(), event);
+        self.flush();
+    }
+
+    // Incrementボタンを1回押したときと同じ動作をする
+    pub fn increment(&mut self) {
+        self.send_counter(CounterEvent::Increment);
+    }
+
+    // Decrementボタンを1回押したときと同じ動作をする
+    pub fn decrement(&mut self) {
+        self.send_counter(CounterEvent::Decrement);
+    }
+
+    // ウィンドウからCounterにイベントが届いたときと同じ動作をする
+    pub fn send_window(&mut self, event: WindowEvent) {
+        self.cx.emit_to(self.counter, event);
+        self.flush();
+    }
+
+    // entityのボタンをクリックしたときと同じように，押した・離したイベントを順に送る
+    // on_press_downとon_pressのコールバックが呼び出される
+    pub fn press(&mut self, entity: Entity) {
+        self.cx
+            .emit_to(entity, WindowEvent::PressDown { mouse: true });
+        self.cx.emit_to(entity, WindowEvent::Press { mouse: true });
+        self.flush();
+    }
+
+    // Counterにフォーカスがあるときにキーを押したときと同じ動作をする
+    pub fn key_down(&mut self, code: Code) {
+        self.send_window(WindowEvent::KeyDown(code, None));
+    }
+
+    // 押されている修飾キーを設定する．Shiftを押しながらの操作などを再現する
+    pub fn set_modifiers(&mut self, modifiers: Modifiers) {
+        *BackendContext::new(&mut self.cx).modifiers() = modifiers;
+    }
+
+    // Counterが使う時計をdurationだけ進める．cooldownや長押し，繰り返しやdebounceの判定はこの時計で行われる
+    // 進めた後は，viziaのタイマーが時刻を確かめるときと同じようにRepeatTickとDebounceElapsedを送る
+    pub fn advance(&mut self, duration: Duration) {
+        self.clock.advance(duration);
+        self.send_counter(CounterEvent::RepeatTick);
+        self.send_counter(CounterEvent::DebounceElapsed);
+    }
+
+    // 現在のcountを読み出す
+    pub fn count(&self) -> i32 {
+        AppData::count.get(&self.cx)
+    }
+
+    // Counterの現在の状態を写し取る
+    pub fn state(&mut self) -> CounterState<i32> {
+        let counter = self.counter;
+        self.cx.with_current(counter, |cx| {
+            cx.data::<Counter<i32>>()
+                .expect("Counter is not built")
+                .state()
+        })
+    }
+
+    // Counterの内部のモデルからlensで値を読み出す
+    // ボタンの並べ方など，Counterが子要素を作るために持っている設定を確かめるときに使う
+    pub fn read<L: Lens>(&mut self, lens: L) -> L::Target
+    where
+        L::Target: Clone,
+    {
+        let counter = self.counter;
+        self.cx.with_current(counter, |cx| lens.get(cx))
+    }
+
+    // Counterが値のLabelに表示している文字列を読み出す
+    pub fn display(&mut self) -> String {
+        let counter = self.counter;
+        self.cx
+            .with_current(counter, |cx| CounterText::display.get(cx))
+    }
+
+    // リセットボタンに表示している文字列を読み出す．確認待ちの間は"Sure?"になる
+    pub fn reset_label(&mut self) -> String {
+        let counter = self.counter;
+        self.cx
+            .with_current(counter, |cx| CounterText::reset_label.get(cx))
+    }
+
+    // show_rateで表示している直近の変化量の文字列を読み出す
+    // 時計を進めただけでは表示は更新されないため，先にsend_counterでRateTickを送る
+    pub fn rate(&mut self) -> String {
+        let counter = self.counter;
+        self.cx
+            .with_current(counter, |cx| CounterText::rate.get(cx))
+    }
+
+    // リセットしたときにスクリーンリーダーが読み上げる文字列を読み出す
+    pub fn announcement(&mut self) -> String {
+        let counter = self.counter;
+        self.cx
+            .with_current(counter, |cx| CounterText::announcement.get(cx))
+    }
+
+    // Counterの子孫のうち，classを持つ要素を作られた順に返す
+    // Bindingで作り直された子要素は古いものが取り除かれているため，現在表示されている要素だけが返る
+    pub fn find_all(&mut self, class: &str) -> Vec<Entity> {
+        let mut found = Vec::new();
+        let mut cx = EventContext::new_with_current(&mut self.cx, self.counter);
+        visit_descendants(&mut cx, &mut |cx, entity| {
+            if cx.has_class(class) {
+                found.push(entity);
+            }
+        });
+        found
+    }
+
+    // Counterの子孫が持つclassesのうちのクラスを，ツリーの順に並べて返す
+    // 横に並べたCounterでは，ツリーの順がそのまま左から右への表示の順となる
+    pub fn class_order(&mut self, classes: &[&'static str]) -> Vec<&'static str> {
+        let mut order = Vec::new();
+        let mut cx = EventContext::new_with_current(&mut self.cx, self.counter);
+        visit_descendants(&mut cx, &mut |cx, _| {
+            if let Some(class) = classes.iter().find(|class| cx.has_class(class)) {
+                order.push(*class);
+            }
+        });
+        order
+    }
+
+    // Counterの子孫のうち，classを持つ最初の要素を返す
+    pub fn find(&mut self, class: &str) -> Option<Entity> {
+        self.find_all(class).into_iter().next()
+    }
+
+    // entityにclassが付与されているかどうか
+    pub fn has_class(&mut self, entity: Entity, class: &str) -> bool {
+        EventContext::new_with_current(&mut self.cx, entity).has_class(class)
+    }
+
+    // スクリーンリーダーに伝えるentityの役割・名前・ライブリージョンの設定を読み出す
+    // role・name・liveのモディファイアで設定されていない項目はNoneとなる
+    pub fn role(&mut self, entity: Entity) -> Option<Role> {
+        BackendContext::new(&mut self.cx)
+            .style()
+            .role
+            .get(entity)
+            .copied()
+    }
+
+    pub fn name(&mut self, entity: Entity) -> Option<String> {
+        BackendContext::new(&mut self.cx)
+            .style()
+            .name
+            .get(entity)
+            .cloned()
+    }
+
+    pub fn live(&mut self, entity: Entity) -> Option<Live> {
+        BackendContext::new(&mut self.cx)
+            .style()
+            .live
+            .get(entity)
+            .copied()
+    }
+
+    // entityにインラインで設定された最小の幅を読み出す．設定されていない場合はNone
+    pub fn min_width(&mut self, entity: Entity) -> Option<Units> {
+        BackendContext::new(&mut self.cx)
+            .style()
+            .min_width
+            .get(entity)
+            .copied()
+    }
+
+    // entityにインラインで設定された背景色を読み出す．設定されていない場合はスタイルシートの色が使われるためNone
+    pub fn background_color(&mut self, entity: Entity) -> Option<Color> {
+        BackendContext::new(&mut self.cx)
+            .style()
+            .background_color
+            .get(entity)
+            .copied()
+    }
+
+    // AppDataの現在の状態を写し取る．操作の前後で取ってdiffで比較する
+    pub fn snapshot(&self) -> AppDataSnapshot {
+        self.cx
+            .data::<AppData>()
+            .expect("AppData is not built")
+            .snapshot()
+    }
+
+    // キューに溜まったイベントを処理し，変化したモデルをバインディングに反映する
+    // Counterのコールバックが送ったAppEventや，バインディングが送ったValueChangedも続けて処理される
+    pub fn flush(&mut self) {
+        for _ in 0..FLUSH_ROUNDS {
+            self.events.flush_events(&mut self.cx, |_| {});
+            BackendContext::new(&mut self.cx).process_data_updates();
+        }
+    }
+
+    pub fn context(&mut self) -> &mut Context {
+        &mut self.cx
+    }
+}
+
+// 現在の要素の子孫を深さ優先でツリーの順に辿り，それぞれを現在の要素としてvisitを呼び出す
+fn visit_descendants(cx: &mut EventContext, visit: &mut dyn FnMut(&mut EventContext, Entity)) {
+    let mut index = 0;
+    while let Some(child) = cx.nth_child(index) {
+        cx.with_current(child, |cx| {
+            visit(cx, child);
+            visit_descendants(cx, visit);
+        });
+        index += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn increments_through_the_harness() {
+        let mut harness = CounterHarness::new(AppData::builder().max(10).build().unwrap());
+        harness.increment();
+        harness.increment();
+        assert_eq!(harness.count(), 2);
+        // モデルの変化はValueChangedでCounterにも伝わる
+        assert_eq!(harness.display(), "2");
+    }
+}